@@ -5,9 +5,9 @@ for disassembly (not reconmmended)
 
 */
 
-use jacob::codegen::{AssemblyInst, Compilation, FuncAsm, Reg, TargetArch};
+use jacob::codegen::{AssemblyInst, Compilation, ConstPool, FuncAsm, Reg, TargetArch};
 use jacob::ir::Module;
-use jacob::ir::visibility::Visibilty;
+use jacob::ir::visibility::{SymbolVisibility, Visibilty};
 use jacob::x86::{RAX, RDI, RSI};
 
 fn main() {
@@ -20,9 +20,20 @@ fn main() {
             meta_insts: Vec::new(), // We can just leave this empty,
             name: "add".to_owned(),
             scope: Visibilty::Public,
-            consts: Vec::new(),
+            symbol_visibility: SymbolVisibility::Default,
+            consts: ConstPool::new(),
+            attrs: Vec::new(),
+            stack_slots: 0,
+            frame_size: 0,
+            saved_regs: Vec::new(),
+            link_slot: None,
+            args: Vec::new(),
+            arg_ranges: Vec::new(),
+            ret: None,
         }],
-        arch: TargetArch::X86,
+        arch: TargetArch::X86.into(),
+        debug_info: false,
+        consts: ConstPool::new(),
     };
 
     let module = Module::decompile_comp(compilation);
@@ -1,4 +1,7 @@
-use crate::{aarch64::Aarch64Backend, codegen::AsmPrinter};
+use crate::{
+    aarch64::Aarch64Backend,
+    codegen::{Allocation, ArchInfos, AsmPrinter, AssemblyInst, constpool::const_label},
+};
 
 macro_rules! reg_printer {
     ($num_var:tt, $ty_var:tt, $num_lit:literal, $b64_name:expr) => {
@@ -15,9 +18,11 @@ impl AsmPrinter for Aarch64Backend {
     fn print_op(&self, op: &crate::codegen::Allocation) -> String {
         match op {
             crate::codegen::Allocation::Register { id, ty } => self.print_reg(id, ty),
-            crate::codegen::Allocation::Stack { slot, ty: _ } => format!("[sp, #-{}]!", slot * 16), // ToDo: check alignment
-            crate::codegen::Allocation::Imm { num, ty: _ } => format!("#{num:x}"),
-            crate::codegen::Allocation::ConstUse { id } => format!("[c{id}]"),
+            crate::codegen::Allocation::Stack { slot, ty: _ } => {
+                format!("[sp, #{}]", self.data_layout().slot_offset(*slot))
+            }
+            crate::codegen::Allocation::Imm { num, ty: _ } => format!("#{num:#x}"),
+            crate::codegen::Allocation::ConstUse { id } => const_label(*id),
         }
     }
 
@@ -56,15 +61,67 @@ impl AsmPrinter for Aarch64Backend {
         panic!("Impossible register id: {num}. Aarch64 supports 0-29");
     }
 
-    fn print_const(&self, c: &crate::codegen::Constant) -> String {
-        format!("c{}: {:?}", c.id, c.bytes)
+    fn print_inst(&self, inst: &AssemblyInst) -> String {
+        // `adrp` loads the page of the constant, the offset inside of the page is added by the load
+        if inst.opcode == "ldr"
+            && let [out, Allocation::ConstUse { id }] = inst.ops[..]
+        {
+            let out = self.print_op(&out);
+            return format!("\tldr {out}, [{out}, :lo12:{}]\n", const_label(id));
+        }
+
+        let mut ops = String::new();
+
+        for (index, op) in inst.ops.iter().enumerate() {
+            if index != 0 {
+                ops += ", ";
+            }
+
+            ops += &self.print_op(op);
+        }
+
+        if let Some(symbol) = &inst.symbol {
+            if !ops.is_empty() {
+                ops += ", ";
+            }
+
+            ops += symbol;
+        }
+
+        format!("\t{} {}\n", inst.opcode, ops)
     }
 
-    fn print_code_section(&self) -> &'static str {
-        ".text\n"
+    fn print_prologue(&self, func: &crate::codegen::FuncAsm) -> String {
+        if func.frame_size == 0 {
+            return String::new();
+        }
+
+        let mut out = format!("\tsub sp, sp, #{}\n", func.frame_size);
+        for (reg, slot) in &func.saved_regs {
+            out += &format!("\tstr {}, {}\n", self.print_op(reg), self.print_op(slot));
+        }
+        if let Some(slot) = &func.link_slot {
+            out += &format!("\tstr x30, {}\n", self.print_op(slot));
+        }
+        out
     }
 
-    fn print_global(&self, func: &String) -> String {
-        format!(".global {}\n", func)
+    fn print_epilogue(&self, func: &crate::codegen::FuncAsm) -> String {
+        if func.frame_size == 0 {
+            return String::new();
+        }
+
+        let mut out = String::new();
+        if let Some(slot) = &func.link_slot {
+            out += &format!("\tldr x30, {}\n", self.print_op(slot));
+        }
+        for (reg, slot) in &func.saved_regs {
+            out += &format!("\tldr {}, {}\n", self.print_op(reg), self.print_op(slot));
+        }
+        out + &format!("\tadd sp, sp, #{}\n", func.frame_size)
+    }
+
+    fn print_code_section(&self) -> &'static str {
+        ".text\n"
     }
 }
@@ -0,0 +1,46 @@
+use crate::{
+    aarch64::*,
+    codegen::{CallConvInfos, CallLowering, Reg},
+    ir::CallingConv,
+};
+
+fn regs(regs: &[Aarch64Reg]) -> Vec<Box<dyn Reg>> {
+    regs.iter().map(|x| Box::new(*x) as Box<dyn Reg>).collect()
+}
+
+impl CallConvInfos for Aarch64Backend {
+    fn callconv_arg_regs(&self, conv: CallingConv) -> Option<Vec<Box<dyn Reg>>> {
+        match conv {
+            CallingConv::C | CallingConv::Aapcs64 | CallingConv::PreserveMost => {
+                Some(regs(&[X0, X1, X2, X3, X4, X5, X6, X7]))
+            }
+            CallingConv::Fast => Some(regs(&[
+                X0, X1, X2, X3, X4, X5, X6, X7, X9, X10, X11, X12, X13, X14, X15,
+            ])),
+            CallingConv::SysV | CallingConv::Win64 | CallingConv::RiscvLp64 => None,
+        }
+    }
+
+    fn callconv_callee_saved(&self, conv: CallingConv) -> Option<Vec<Box<dyn Reg>>> {
+        match conv {
+            CallingConv::C | CallingConv::Aapcs64 | CallingConv::Fast => {
+                Some(regs(&[X19, X20, X21, X22, X23, X24, X25, X26, X27, X28]))
+            }
+            // x0 - x8 and the intra procedure call registers (x16 - x18) are clobbered
+            CallingConv::PreserveMost => Some(regs(&[
+                X9, X10, X11, X12, X13, X14, X15, X19, X20, X21, X22, X23, X24, X25, X26, X27, X28,
+            ])),
+            CallingConv::SysV | CallingConv::Win64 | CallingConv::RiscvLp64 => None,
+        }
+    }
+}
+
+impl CallLowering for Aarch64Backend {
+    fn call_opcode(&self) -> &'static str {
+        "bl"
+    }
+
+    fn call_uses_link_reg(&self) -> bool {
+        true
+    }
+}
@@ -2,7 +2,7 @@ use procmacro::patterns;
 
 use crate::{
     aarch64::{Aarch64Backend, X0},
-    codegen::{BackendInst, InstrincLowering, Reg},
+    codegen::{AssemblyInst, BackendInst, InstrincLowering, Reg},
 };
 
 impl BackendInst for Aarch64Backend {
@@ -10,6 +10,9 @@ impl BackendInst for Aarch64Backend {
         Add(Gr, Gr) -> Gr {
             asm: add (out, in1, in2)
         }
+        Add(Gr, Imm) -> Gr {
+            asm: add (out, in1, in2)
+        }
         Ret(Gr) {
             condition: in1 == X0.alloc()
             asm: ret()
@@ -22,6 +25,27 @@ impl BackendInst for Aarch64Backend {
         Copy(Gr) -> Gr {
             asm: mov (out, in1)
         }
+        Copy(Imm) -> Gr {
+            asm: mov (out, in1)
+        }
+        Copy(ConstUse) -> Gr {
+            asm: adrp (out, in1)
+            asm: ldr (out, in1)
+        }
+        Copy(Gr) -> Mem {
+            asm: str (in1, out)
+        }
+        Copy(Mem) -> Gr {
+            asm: ldr (out, in1)
+        }
+    }
+
+    fn is_legal_imm(&self, num: usize, _ty: crate::ir::TypeMetadata) -> bool {
+        num < 4096
+    }
+
+    fn is_noop(&self, inst: &AssemblyInst) -> bool {
+        inst.opcode() == "mov" && inst.ops().len() == 2 && inst.ops()[0] == inst.ops()[1]
     }
 }
 
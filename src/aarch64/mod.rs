@@ -2,15 +2,19 @@
 
 use procmacro::backend;
 
-use crate::codegen::{Allocation, ArchBackend, ArchInfos, BackendDecompiler, Reg};
+use crate::codegen::{DataLayout, DebugInfos, Reg};
 
 mod asmprinter;
+mod callconv;
 mod lowering;
 
+const LAYOUT: DataLayout = DataLayout::LP64;
+
 backend! {
     name: Aarch64,
     ret_reg: X0,
     stack_reg: SP,
+    data_layout: LAYOUT,
 
     caller_saved: [ X0, X1, X2, X3, X4, X5, X6, X7 ],
     callee_saved: [
@@ -36,3 +40,18 @@ backend! {
 
     stack_off: -5,
 }
+
+impl DebugInfos for Aarch64Backend {
+    fn dwarf_reg(&self, id: usize) -> Option<u16> {
+        if id == SP.id() {
+            return Some(self.dwarf_stack_reg());
+        }
+
+        // x0 - x28 have the ids 0 - 28
+        Some(id as u16)
+    }
+
+    fn dwarf_stack_reg(&self) -> u16 {
+        31
+    }
+}
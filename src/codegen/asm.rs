@@ -1,10 +1,15 @@
-use crate::{codegen::Allocation, ir::TypeMetadata};
+use crate::{
+    codegen::Allocation,
+    ir::{SourceLoc, TypeMetadata},
+};
 
 /// Structure to store an assembly instruction
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AssemblyInst {
     pub(crate) ops: Vec<Allocation>,
     pub(crate) opcode: String,
+    pub(crate) loc: Option<SourceLoc>,
+    pub(crate) symbol: Option<String>,
 }
 
 impl AssemblyInst {
@@ -13,6 +18,8 @@ impl AssemblyInst {
         Self {
             ops: Vec::new(),
             opcode: opcode.to_owned(),
+            loc: None,
+            symbol: None,
         }
     }
 
@@ -21,6 +28,8 @@ impl AssemblyInst {
         Self {
             ops: vec![*op0],
             opcode: opcode.to_owned(),
+            loc: None,
+            symbol: None,
         }
     }
 
@@ -29,6 +38,8 @@ impl AssemblyInst {
         Self {
             ops: vec![*op0, *op1],
             opcode: opcode.to_owned(),
+            loc: None,
+            symbol: None,
         }
     }
 
@@ -37,6 +48,8 @@ impl AssemblyInst {
         Self {
             ops: vec![*op0, *op1, *op2],
             opcode: opcode.to_owned(),
+            loc: None,
+            symbol: None,
         }
     }
 
@@ -45,14 +58,46 @@ impl AssemblyInst {
         Self {
             opcode: opcode.to_owned(),
             ops: ops.iter().map(|x| **x).collect(),
+            loc: None,
+            symbol: None,
         }
     }
 
+    /// Creates a new assembly instruction which has a symbol (e.g: a function name) as operand
+    pub fn with_symbol(opcode: &str, symbol: &str) -> Self {
+        Self {
+            ops: Vec::new(),
+            opcode: opcode.to_owned(),
+            loc: None,
+            symbol: Some(symbol.to_owned()),
+        }
+    }
+
+    /// Returns the mnemonic of the instruction
+    pub fn opcode(&self) -> &str {
+        &self.opcode
+    }
+
+    /// Returns the operands of the instruction
+    pub fn ops(&self) -> &[Allocation] {
+        &self.ops
+    }
+
+    /// Returns the symbol operand of the instruction
+    pub fn symbol(&self) -> Option<&str> {
+        self.symbol.as_deref()
+    }
+
     /// Returns the number of operands the function has
     pub fn operands(&self) -> usize {
         self.ops.len()
     }
 
+    /// Returns the source location the instruction was generated for
+    pub fn loc(&self) -> Option<&SourceLoc> {
+        self.loc.as_ref()
+    }
+
     /// Returns the type of the assembly instruction (based on the first operand)
     pub fn get_ty(&self) -> Option<TypeMetadata> {
         if self.ops.is_empty() {
@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+
+use crate::{
+    codegen::{DataLayout, Endianness},
+    ir::TypeMetadata,
+};
+
+/// Returns the label of the constant with the given id
+pub(crate) fn const_label(id: usize) -> String {
+    format!(".Ljacob_const{id}")
+}
+
+/// A constant
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constant {
+    /// The value of the constnat
+    pub bytes: Vec<u8>,
+    /// The id of the constant
+    pub id: usize,
+    /// The alignment of the constant in bytes
+    pub align: usize,
+}
+
+/// Stores read only constants, every value is only stored once
+///
+/// Example:
+/// ```rust
+/// use jacob::codegen::{ConstPool, DataLayout};
+/// use jacob::ir::TypeMetadata;
+///
+/// let layout = DataLayout::LP64;
+/// let mut pool = ConstPool::new();
+///
+/// let a = pool.add_int(0x1234_5678_9abc, TypeMetadata::Int64, &layout);
+/// let b = pool.add_bytes(&[1, 2, 3], 1);
+/// let c = pool.add_int(0x1234_5678_9abc, TypeMetadata::Int64, &layout);
+///
+/// assert_eq!(a, c);
+/// assert_ne!(a, b);
+/// assert_eq!(pool.len(), 2);
+/// assert_eq!(pool.get(a).unwrap().align, 8);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConstPool {
+    consts: Vec<Constant>,
+    ids: HashMap<Vec<u8>, usize>,
+}
+
+impl ConstPool {
+    /// Creates a new empty constant pool
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the bytes into the pool and returns the id of the constant
+    ///
+    /// If the same bytes are already in the pool, the existing constant is reused
+    /// (and its alignment is raised if required)
+    pub fn add_bytes(&mut self, bytes: &[u8], align: usize) -> usize {
+        assert!(
+            align.is_power_of_two(),
+            "The alignment must be a power of two"
+        );
+
+        if let Some(id) = self.ids.get(bytes) {
+            let c = &mut self.consts[*id];
+            c.align = c.align.max(align);
+            return *id;
+        }
+
+        let id = self.consts.len();
+        self.consts.push(Constant {
+            bytes: bytes.to_vec(),
+            id,
+            align,
+        });
+        self.ids.insert(bytes.to_vec(), id);
+
+        id
+    }
+
+    /// Adds the integer with the byte order and natural alignment of the target
+    pub fn add_int(&mut self, num: u64, ty: TypeMetadata, layout: &DataLayout) -> usize {
+        let size = layout.size_of(ty);
+        let bytes = match layout.endianness() {
+            Endianness::Little => num.to_le_bytes()[..size].to_vec(),
+            Endianness::Big => num.to_be_bytes()[8 - size..].to_vec(),
+        };
+
+        self.add_bytes(&bytes, layout.align_of(ty))
+    }
+
+    /// Adds the 64 bit floating point number with the byte order of the target
+    pub fn add_f64(&mut self, num: f64, layout: &DataLayout) -> usize {
+        let bytes = match layout.endianness() {
+            Endianness::Little => num.to_le_bytes(),
+            Endianness::Big => num.to_be_bytes(),
+        };
+
+        self.add_bytes(&bytes, 8)
+    }
+
+    /// Adds the 32 bit floating point number with the byte order of the target
+    pub fn add_f32(&mut self, num: f32, layout: &DataLayout) -> usize {
+        let bytes = match layout.endianness() {
+            Endianness::Little => num.to_le_bytes(),
+            Endianness::Big => num.to_be_bytes(),
+        };
+
+        self.add_bytes(&bytes, 4)
+    }
+
+    /// Adds all constants of the other pool and returns the new ids of them
+    /// (indexed by their old id)
+    pub fn merge(&mut self, other: &ConstPool) -> Vec<usize> {
+        other
+            .consts
+            .iter()
+            .map(|c| self.add_bytes(&c.bytes, c.align))
+            .collect()
+    }
+
+    /// Returns the constant with the id
+    pub fn get(&self, id: usize) -> Option<&Constant> {
+        self.consts.get(id)
+    }
+
+    /// Returns the number of constants
+    pub fn len(&self) -> usize {
+        self.consts.len()
+    }
+
+    /// Returns if the pool contains no constants
+    pub fn is_empty(&self) -> bool {
+        self.consts.is_empty()
+    }
+
+    /// Returns an iterator over the constants
+    pub fn iter(&self) -> impl Iterator<Item = &Constant> {
+        self.consts.iter()
+    }
+}
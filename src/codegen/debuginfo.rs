@@ -0,0 +1,400 @@
+use std::fmt::Write;
+
+use crate::{
+    codegen::{Allocation, ArchBackend, Compilation, FuncAsm},
+    ir::TypeMetadata,
+};
+
+/// The dwarf version which is emitted
+const DWARF_VERSION: u16 = 4;
+
+/// Abbreviation codes
+const ABBREV_CU: u8 = 1;
+const ABBREV_CU_NO_LINES: u8 = 2;
+const ABBREV_BASE_TYPE: u8 = 3;
+const ABBREV_SUBPROGRAM: u8 = 4;
+const ABBREV_SUBPROGRAM_TYPED: u8 = 5;
+const ABBREV_PARAM: u8 = 6;
+
+/// An abbreviation: (code, tag, has children, [(attribute, form)])
+type Abbrev = (u8, u16, bool, &'static [(u16, u16)]);
+
+/// Returns the label which marks the end of the function with the given index
+pub(crate) fn func_end_label(index: usize) -> String {
+    format!(".Ljacob_func_end{index}")
+}
+
+/// Returns the label of the instruction `inst` of the function with the given index
+pub(crate) fn inst_label(index: usize, inst: usize) -> String {
+    format!(".Ljacob_func{index}_inst{inst}")
+}
+
+/// Helper structure to emit dwarf debug info for a compilation
+///
+/// It emits the `.debug_abbrev`, `.debug_info` and `.debug_loc` sections. The
+/// `.debug_line` section is generated by the assembler from the `.file`/`.loc` directives
+///
+/// The locations of the arguments are location lists, because the allocations of
+/// the arguments are reused once they aren't needed anymore
+///
+/// Example:
+/// ```rust
+/// use jacob::codegen::TargetArch;
+/// use jacob::ir::{Function, Module, TypeMetadata};
+///
+/// let mut func = Function::new("id");
+/// let x = func.add_arg(TypeMetadata::Int64);
+/// func.set_ret(TypeMetadata::Int64);
+/// func.set_loc("id.src", 1, 1);
+/// func.ret(&x);
+///
+/// let mut module = Module::new();
+/// module.add_func(func);
+///
+/// let mut compilation = module.compile(TargetArch::X86, false);
+/// compilation.debug_info = true;
+///
+/// let asm = compilation.asm();
+/// assert!(asm.contains(".section .debug_info"));
+/// assert!(asm.contains(".asciz \"arg0\""));
+/// assert!(asm.contains(".section .debug_loc"));
+/// ```
+pub struct DebugInfo<'a> {
+    backend: &'a dyn ArchBackend,
+    out: String,
+}
+
+impl<'a> DebugInfo<'a> {
+    /// Creates a new debug info emitter
+    pub fn new(backend: &'a dyn ArchBackend) -> Self {
+        Self {
+            backend,
+            out: String::new(),
+        }
+    }
+
+    /// Prints the debug info sections for the compilation
+    pub fn print(mut self, compilation: &Compilation) -> String {
+        let file = compilation
+            .funcs
+            .iter()
+            .flat_map(|func| func.insts.iter())
+            .find_map(|inst| inst.loc())
+            .map(|loc| loc.file.to_string());
+
+        self.print_abbrevs();
+
+        self.line(".section .debug_info,\"\",@progbits");
+        self.label(".Ldebug_info_start");
+        self.line(".long .Ldebug_info_end - .Ldebug_info_start - 4");
+        self.line(&format!(".short {DWARF_VERSION}"));
+        self.line(".long .Ldebug_abbrev_start");
+        self.line(".byte 8");
+
+        match &file {
+            Some(file) => {
+                self.uleb(ABBREV_CU as u64);
+                self.string("jacob");
+                self.line(".short 0xc"); // DW_LANG_C99
+                self.string(file);
+                self.line(".long .Ldebug_line_start");
+            }
+            None => {
+                self.uleb(ABBREV_CU_NO_LINES as u64);
+                self.string("jacob");
+                self.line(".short 0xc"); // DW_LANG_C99
+                self.string(&self.backend.name().to_lowercase());
+            }
+        }
+
+        let mut types: Vec<TypeMetadata> = Vec::new();
+        for func in &compilation.funcs {
+            for ty in func.args.iter().map(|arg| arg.get_ty()).chain(func.ret) {
+                if !types.contains(&ty) {
+                    types.push(ty);
+                }
+            }
+        }
+
+        for ty in &types {
+            self.print_base_type(ty);
+        }
+
+        for (index, func) in compilation.funcs.iter().enumerate() {
+            if !func.scope.is_declaration() {
+                self.print_subprogram(index, func);
+            }
+        }
+
+        self.line(".byte 0");
+        self.label(".Ldebug_info_end");
+
+        self.line(".section .debug_loc,\"\",@progbits");
+        for (index, func) in compilation.funcs.iter().enumerate() {
+            if !func.scope.is_declaration() {
+                self.print_loc_lists(index, func);
+            }
+        }
+
+        if file.is_some() {
+            self.line(".section .debug_line,\"\",@progbits");
+            self.label(".Ldebug_line_start");
+        }
+
+        self.out
+    }
+
+    /// Prints the `.debug_abbrev` section
+    fn print_abbrevs(&mut self) {
+        let abbrevs: [Abbrev; 6] = [
+            // DW_TAG_compile_unit: producer, language, name, stmt_list
+            (
+                ABBREV_CU,
+                0x11,
+                true,
+                &[(0x25, 0x08), (0x13, 0x05), (0x03, 0x08), (0x10, 0x17)],
+            ),
+            // DW_TAG_compile_unit: producer, language, name
+            (
+                ABBREV_CU_NO_LINES,
+                0x11,
+                true,
+                &[(0x25, 0x08), (0x13, 0x05), (0x03, 0x08)],
+            ),
+            // DW_TAG_base_type: name, encoding, byte_size
+            (
+                ABBREV_BASE_TYPE,
+                0x24,
+                false,
+                &[(0x03, 0x08), (0x3e, 0x0b), (0x0b, 0x0b)],
+            ),
+            // DW_TAG_subprogram: name, low_pc, high_pc, external
+            (
+                ABBREV_SUBPROGRAM,
+                0x2e,
+                true,
+                &[(0x03, 0x08), (0x11, 0x01), (0x12, 0x06), (0x3f, 0x0c)],
+            ),
+            // DW_TAG_subprogram: name, low_pc, high_pc, external, type
+            (
+                ABBREV_SUBPROGRAM_TYPED,
+                0x2e,
+                true,
+                &[
+                    (0x03, 0x08),
+                    (0x11, 0x01),
+                    (0x12, 0x06),
+                    (0x3f, 0x0c),
+                    (0x49, 0x13),
+                ],
+            ),
+            // DW_TAG_formal_parameter: name, type, location (list)
+            (
+                ABBREV_PARAM,
+                0x05,
+                false,
+                &[(0x03, 0x08), (0x49, 0x13), (0x02, 0x17)],
+            ),
+        ];
+
+        self.line(".section .debug_abbrev,\"\",@progbits");
+        self.label(".Ldebug_abbrev_start");
+
+        for (code, tag, children, attrs) in abbrevs {
+            self.uleb(code as u64);
+            self.uleb(tag as u64);
+            self.line(&format!(".byte {}", children as u8));
+
+            for (attr, form) in attrs {
+                self.uleb(*attr as u64);
+                self.uleb(*form as u64);
+            }
+
+            self.line(".byte 0");
+            self.line(".byte 0");
+        }
+
+        self.line(".byte 0");
+    }
+
+    /// Prints the base type entry for the type
+    fn print_base_type(&mut self, ty: &TypeMetadata) {
+        self.label(&DebugInfo::type_label(ty));
+        self.uleb(ABBREV_BASE_TYPE as u64);
+        self.string(&format!("{ty:?}").to_lowercase());
+
+        // DW_ATE_signed / DW_ATE_unsigned
+        let encoding = if ty.signed() { 0x05 } else { 0x08 };
+        self.line(&format!(".byte {encoding}"));
+        self.line(&format!(".byte {}", ty.bitwidth() / 8));
+    }
+
+    /// Prints the subprogram entry (and the parameters) for the function
+    fn print_subprogram(&mut self, index: usize, func: &FuncAsm) {
+        let abbrev = match func.ret {
+            Some(_) => ABBREV_SUBPROGRAM_TYPED,
+            None => ABBREV_SUBPROGRAM,
+        };
+
+        self.uleb(abbrev as u64);
+        self.string(&func.name);
+        self.line(&format!(".quad {}", func.name));
+        self.line(&format!(".long {} - {}", func_end_label(index), func.name));
+        self.line(&format!(".byte {}", func.scope.is_exported() as u8));
+
+        if let Some(ret) = &func.ret {
+            self.type_ref(ret);
+        }
+
+        for (num, arg) in func.args.iter().enumerate() {
+            self.uleb(ABBREV_PARAM as u64);
+            self.string(&format!("arg{num}"));
+            self.type_ref(&arg.get_ty());
+            self.line(&format!(".long {}", DebugInfo::loc_list_label(index, num)));
+        }
+
+        self.line(".byte 0");
+    }
+
+    /// Returns the label of the location list of the argument
+    fn loc_list_label(index: usize, arg: usize) -> String {
+        format!(".Ldebug_loc{index}_{arg}")
+    }
+
+    /// Prints the location lists of the arguments of the function
+    ///
+    /// The addresses are relative to the start of the function
+    fn print_loc_lists(&mut self, index: usize, func: &FuncAsm) {
+        let label = |inst: usize| match inst == func.insts.len() {
+            true => func_end_label(index),
+            false => inst_label(index, inst),
+        };
+
+        for (num, ranges) in func.arg_ranges.iter().enumerate() {
+            self.label(&DebugInfo::loc_list_label(index, num));
+
+            // selects the function as the base address
+            self.line(".quad 0xffffffffffffffff");
+            self.line(&format!(".quad {}", func.name));
+
+            for range in ranges {
+                let expr = self.location(&range.alloc);
+                if expr.is_empty() {
+                    continue;
+                }
+
+                self.line(&format!(".quad {} - {}", label(range.start), func.name));
+                self.line(&format!(".quad {} - {}", label(range.end), func.name));
+                self.line(&format!(".short {}", expr.len()));
+                for byte in expr {
+                    self.line(&format!(".byte {byte:#x}"));
+                }
+            }
+
+            self.line(".quad 0");
+            self.line(".quad 0");
+        }
+    }
+
+    /// Returns the dwarf location expression for the allocation
+    fn location(&self, alloc: &Allocation) -> Vec<u8> {
+        let mut expr = Vec::new();
+
+        match alloc {
+            Allocation::Register { id, .. } => {
+                let Some(reg) = self.backend.dwarf_reg(*id) else {
+                    return expr;
+                };
+
+                if reg < 32 {
+                    expr.push(0x50 + reg as u8); // DW_OP_reg<n>
+                } else {
+                    expr.push(0x90); // DW_OP_regx
+                    uleb128(&mut expr, reg as u64);
+                }
+            }
+            Allocation::Stack { slot, .. } => {
+                let reg = self.backend.dwarf_stack_reg();
+
+                if reg < 32 {
+                    expr.push(0x70 + reg as u8); // DW_OP_breg<n>
+                } else {
+                    expr.push(0x92); // DW_OP_bregx
+                    uleb128(&mut expr, reg as u64);
+                }
+
+                sleb128(
+                    &mut expr,
+                    self.backend.data_layout().slot_offset(*slot) as i64,
+                );
+            }
+            Allocation::Imm { num, .. } => {
+                expr.push(0x10); // DW_OP_constu
+                uleb128(&mut expr, *num as u64);
+                expr.push(0x9f); // DW_OP_stack_value
+            }
+            Allocation::ConstUse { .. } => {}
+        }
+
+        expr
+    }
+
+    /// Returns the label of the base type entry
+    fn type_label(ty: &TypeMetadata) -> String {
+        format!(".Ldebug_ty_{ty:?}")
+    }
+
+    /// Prints a reference to a base type entry
+    fn type_ref(&mut self, ty: &TypeMetadata) {
+        let label = DebugInfo::type_label(ty);
+        self.line(&format!(".long {label} - .Ldebug_info_start"));
+    }
+
+    fn uleb(&mut self, num: u64) {
+        self.line(&format!(".uleb128 {num:#x}"));
+    }
+
+    fn string(&mut self, text: &str) {
+        let text = text.replace('\\', "\\\\").replace('"', "\\\"");
+        self.line(&format!(".asciz \"{text}\""));
+    }
+
+    fn label(&mut self, name: &str) {
+        let _ = writeln!(self.out, "{name}:");
+    }
+
+    fn line(&mut self, text: &str) {
+        let _ = writeln!(self.out, "\t{text}");
+    }
+}
+
+/// Encodes the number as unsigned leb128
+fn uleb128(out: &mut Vec<u8>, mut num: u64) {
+    loop {
+        let byte = (num & 0x7f) as u8;
+        num >>= 7;
+
+        if num == 0 {
+            out.push(byte);
+            return;
+        }
+
+        out.push(byte | 0x80);
+    }
+}
+
+/// Encodes the number as signed leb128
+fn sleb128(out: &mut Vec<u8>, mut num: i64) {
+    loop {
+        let byte = (num & 0x7f) as u8;
+        num >>= 7;
+
+        let done = (num == 0 && byte & 0x40 == 0) || (num == -1 && byte & 0x40 != 0);
+        if done {
+            out.push(byte);
+            return;
+        }
+
+        out.push(byte | 0x80);
+    }
+}
@@ -0,0 +1,56 @@
+use std::{collections::HashMap, fmt::Write};
+
+use crate::{
+    codegen::{AllocatedIrNode, Allocation},
+    ir::escape,
+};
+
+/// Renders the allocated ir in the graphviz dot format
+///
+/// Every node shows the `Allocation` it got, edges go from the node which last
+/// wrote an allocation to the nodes which read it. Allocations which are read
+/// before they are written (e.g: arguments) come from the `entry` node
+pub fn allocated_ir_to_dot(name: &str, ir: &[AllocatedIrNode]) -> String {
+    let mut out = String::new();
+    let mut defs: HashMap<Allocation, String> = HashMap::new();
+    let mut has_entry = false;
+
+    for (id, node) in ir.iter().enumerate() {
+        let _ = writeln!(out, "\tn{id} [label=\"{}\"];", escape(&node.to_string()));
+
+        for (index, op) in node.ops.iter().enumerate() {
+            if op.is_imm() {
+                continue;
+            }
+
+            let from = match defs.get(op) {
+                Some(def) => def.to_owned(),
+                None => {
+                    has_entry = true;
+                    "entry".to_owned()
+                }
+            };
+
+            let _ = writeln!(
+                out,
+                "\t{from} -> n{id} [label=\"{index}: {}\"];",
+                escape(&op.to_string())
+            );
+        }
+
+        if let Some(alloc) = node.alloc {
+            defs.insert(alloc, format!("n{id}"));
+        }
+    }
+
+    let entry = if has_entry {
+        "\tentry [shape=ellipse];\n"
+    } else {
+        ""
+    };
+
+    format!(
+        "digraph \"{}\" {{\n\tnode [shape=box];\n{entry}{out}}}\n",
+        escape(name)
+    )
+}
@@ -1,52 +1,74 @@
-use crate::ir::IrOperand;
-use std::rc::Rc;
+use crate::{
+    ir::{Function, IrOperand, ValueMap, ir_to_dot},
+    opt::{AnalysisManager, UseCounts, walk_nodes},
+};
+use std::sync::Arc;
 
 /// Helper structure to insert resource dropping instructions in the ir
 /// which will make the register allocators work easier
-pub struct Dropper {
+///
+/// The function itself isn't modified, the drops are inserted into a copy of its ir
+pub struct Dropper<'a> {
+    func: &'a Function,
     ir: Vec<IrOperand>,
 }
 
-impl Dropper {
+impl<'a> Dropper<'a> {
     /// Creates a new dropper
-    pub fn new(ir: Vec<IrOperand>) -> Self {
-        Self { ir }
+    pub fn new(func: &'a Function) -> Self {
+        Self {
+            func,
+            ir: Vec::new(),
+        }
     }
 
-    /// Inserts dropping instructions into the ir
-    pub fn run(&mut self) {
-        // Tracks how many times each Out is used
-        use std::collections::HashMap;
-        let mut use_count: HashMap<u64, usize> = HashMap::new();
-
-        // First pass: count uses of each Out
-        for op in &self.ir {
-            if let IrOperand::Out(node) = op {
-                for inner in &node.borrow().ops {
-                    if inner.is_out() {
-                        let id = inner.hash_u64();
-                        *use_count.entry(id).or_insert(0) += 1;
-                    }
-                }
-            }
-        }
+    /// Inserts dropping instructions into a copy of the ir
+    ///
+    /// The use counts of the function are taken from the analysis manager
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::codegen::Dropper;
+    /// use jacob::ir::{Function, TypeMetadata};
+    /// use jacob::opt::AnalysisManager;
+    ///
+    /// let mut func = Function::new("double");
+    /// let x = func.add_arg(TypeMetadata::Int64);
+    /// let sum = func.add(&x, &x);
+    /// func.ret(&sum);
+    ///
+    /// let mut analyses = AnalysisManager::new();
+    ///
+    /// let mut dropper = Dropper::new(&func);
+    /// dropper.run(&mut analyses);
+    ///
+    /// let ret = dropper.get_ir()[1].node().unwrap().borrow();
+    /// assert!(ret.ops()[0].is_drop());
+    /// // the function keeps its ir
+    /// assert!(!func.ir()[1].node().unwrap().borrow().ops()[0].is_drop());
+    /// ```
+    pub fn run(&mut self, analyses: &mut AnalysisManager) {
+        let mut uses = analyses.get::<UseCounts>(self.func).clone();
+
+        let mut map = ValueMap::new();
+        self.ir = map.remap_all(&self.func.ir);
 
-        // Second pass: replace last uses with Drop
-        for op in &mut self.ir {
-            if let IrOperand::Out(node) = op {
-                let mut n = node.borrow_mut();
-                for inner in n.ops.iter_mut() {
-                    if inner.is_out() {
-                        let id = inner.hash_u64();
-                        if let Some(count) = use_count.get_mut(&id) {
-                            if *count == 1 {
-                                // last use: wrap in Drop
-                                let old = inner.clone();
-                                *inner = IrOperand::Drop(Rc::new(old));
-                            }
-                            *count -= 1;
-                        }
-                    }
+        // the last use of every node output gets wrapped in a drop
+        for node in walk_nodes(&self.func.ir) {
+            let copy = map
+                .get(&IrOperand::Out(node.clone()))
+                .and_then(|op| op.node())
+                .expect("every node of the function is copied")
+                .clone();
+
+            for (index, op) in node.borrow().ops.iter().enumerate() {
+                let Some(used) = op.node() else {
+                    continue;
+                };
+
+                if uses.decrement(used) == 0 {
+                    let mut copy = copy.borrow_mut();
+                    copy.ops[index] = IrOperand::Drop(Arc::new(copy.ops[index].clone()));
                 }
             }
         }
@@ -56,4 +78,9 @@ impl Dropper {
     pub fn get_ir(&self) -> &Vec<IrOperand> {
         &self.ir
     }
+
+    /// Renders the modified ir in the graphviz dot format
+    pub fn to_dot(&self, name: &str) -> String {
+        ir_to_dot(name, &self.ir)
+    }
 }
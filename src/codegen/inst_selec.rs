@@ -1,6 +1,14 @@
+use std::collections::HashMap;
+
 use crate::{
-    codegen::{AllocatedIrNode, ArchBackend, AssemblyInst, CommentedInst},
-    ir::{IrOpcode, visibility::Visibilty},
+    codegen::{
+        AllocatedIrNode, Allocation, ArchBackend, AssemblyInst, CommentedInst, ConstPool,
+        DataLayout, LocRange,
+    },
+    ir::{
+        CallingConv, FunctionAttribute, IrOpcode, TypeMetadata,
+        visibility::{SymbolVisibility, Visibilty},
+    },
 };
 
 /// Stores the assembly for a function
@@ -9,22 +17,31 @@ pub struct FuncAsm {
     /// The instructions of the function
     pub insts: Vec<AssemblyInst>,
     /// The constants the function requires
-    pub consts: Vec<Constant>,
+    pub consts: ConstPool,
     /// Commented instructions
     pub meta_insts: Vec<CommentedInst>,
     /// The name of the function
     pub name: String,
     /// The visibility of the function
     pub scope: Visibilty,
-}
-
-/// A constant
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Constant {
-    /// The value of the constnat
-    pub bytes: Vec<u8>,
-    /// The id of the constant
-    pub id: usize,
+    /// The visibility of the symbol outside of its shared library
+    pub symbol_visibility: SymbolVisibility,
+    /// The attributes of the function
+    pub attrs: Vec<FunctionAttribute>,
+    /// The number of stack slots the function uses
+    pub stack_slots: usize,
+    /// The size of the stack frame in bytes
+    pub frame_size: usize,
+    /// The callee saved registers the function uses and the slots they are preserved in
+    pub saved_regs: Vec<(Allocation, Allocation)>,
+    /// The slot the link register is preserved in (if the function calls others)
+    pub link_slot: Option<Allocation>,
+    /// The allocations of the arguments on entry of the function
+    pub args: Vec<Allocation>,
+    /// The instructions in which the arguments are stored in an allocation
+    pub arg_ranges: Vec<Vec<LocRange>>,
+    /// The return type of the function
+    pub ret: Option<TypeMetadata>,
 }
 
 impl FuncAsm {
@@ -32,10 +49,19 @@ impl FuncAsm {
     pub fn new(name: String, scope: &Visibilty) -> Self {
         Self {
             insts: Vec::new(),
-            consts: Vec::new(),
+            consts: ConstPool::new(),
             meta_insts: Vec::new(),
             name,
             scope: *scope,
+            symbol_visibility: SymbolVisibility::Default,
+            attrs: Vec::new(),
+            stack_slots: 0,
+            frame_size: 0,
+            saved_regs: Vec::new(),
+            link_slot: None,
+            args: Vec::new(),
+            arg_ranges: Vec::new(),
+            ret: None,
         }
     }
 
@@ -44,17 +70,30 @@ impl FuncAsm {
         self.insts.extend_from_slice(inst);
     }
 
-    /// Adds a new constant to the `FuncAsm`
-    pub fn add_const(&mut self, c: Constant) {
-        self.consts.push(c);
+    /// Adds a new constant to the `FuncAsm` and returns its id
+    pub fn add_const(&mut self, bytes: &[u8], align: usize) -> usize {
+        self.consts.add_bytes(bytes, align)
     }
 }
 
+/// How the instruction selector emits the lowered instructions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ISelStrategy {
+    /// Emits the instructions exactly like the backend lowered them
+    #[default]
+    Direct,
+    /// Removes instructions which don't do anything (like moves of a register into itself)
+    Peephole,
+}
+
 /// Helper structure for instructiopn selection
 pub struct InstSelector<'a, 'b> {
     ir: &'a Vec<AllocatedIrNode>,
     backend: &'b dyn ArchBackend,
     rich_commenting: bool,
+    callconvs: Option<&'b HashMap<String, CallingConv>>,
+    strategy: ISelStrategy,
+    starts: Vec<usize>,
 }
 
 impl<'a, 'b> InstSelector<'a, 'b> {
@@ -68,17 +107,56 @@ impl<'a, 'b> InstSelector<'a, 'b> {
             ir,
             backend,
             rich_commenting,
+            callconvs: None,
+            strategy: ISelStrategy::default(),
+            starts: Vec::new(),
         }
     }
 
+    /// Sets the calling conventions of the functions which can be called
+    ///
+    /// Calls to functions which aren't in the map use the default calling convention
+    pub fn with_callconvs(mut self, callconvs: &'b HashMap<String, CallingConv>) -> Self {
+        self.callconvs = Some(callconvs);
+        self
+    }
+
+    /// Sets the strategy used to emit the instructions
+    pub fn with_strategy(mut self, strategy: ISelStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
     /// Runs the register selector
     pub fn run(&mut self, funcasm: &mut FuncAsm) {
+        let layout = self.backend.data_layout();
+
         for ir_inst in self.ir {
-            let inst = if matches!(ir_inst.opcode, IrOpcode::InstrincCall(_)) {
-                self.backend.lower_instrinc(ir_inst)
-            } else {
-                self.backend.lower_inst(ir_inst)
+            let ir_inst = &self.legalize_imms(ir_inst, funcasm, &layout);
+            self.starts.push(funcasm.insts.len());
+
+            let mut inst = match ir_inst.opcode {
+                IrOpcode::InstrincCall(_) => self.backend.lower_instrinc(ir_inst),
+                IrOpcode::Call => {
+                    let conv = ir_inst
+                        .callee
+                        .as_ref()
+                        .and_then(|callee| self.callconvs?.get(callee))
+                        .copied()
+                        .unwrap_or_default();
+
+                    self.backend.lower_call(ir_inst, conv)
+                }
+                _ => self.backend.lower_inst(ir_inst),
             };
+
+            if self.strategy == ISelStrategy::Peephole {
+                inst.retain(|asm| !self.backend.is_noop(asm));
+            }
+
+            for asm in &mut inst {
+                asm.loc = ir_inst.loc.clone();
+            }
             funcasm.add(&inst);
 
             if self.rich_commenting {
@@ -89,4 +167,45 @@ impl<'a, 'b> InstSelector<'a, 'b> {
             }
         }
     }
+
+    /// Converts a range of allocated nodes into the range of the instructions they were
+    /// lowered to (a range which reaches the end of the function ends before the return,
+    /// because the epilogue restores the callee saved registers)
+    pub fn inst_range(&self, range: &LocRange, funcasm: &FuncAsm) -> LocRange {
+        let inst = |node: usize| match self.starts.get(node) {
+            Some(inst) => *inst,
+            None => funcasm
+                .insts
+                .iter()
+                .position(|inst| inst.opcode == "ret")
+                .unwrap_or(funcasm.insts.len()),
+        };
+
+        LocRange {
+            start: inst(range.start),
+            end: inst(range.end),
+            alloc: range.alloc,
+        }
+    }
+
+    /// Moves immediates which can't be encoded by the target into the constant pool
+    fn legalize_imms(
+        &self,
+        node: &AllocatedIrNode,
+        funcasm: &mut FuncAsm,
+        layout: &DataLayout,
+    ) -> AllocatedIrNode {
+        let mut node = node.clone();
+
+        for op in &mut node.ops {
+            if let Allocation::Imm { num, ty } = *op
+                && !self.backend.is_legal_imm(num, ty)
+            {
+                let id = funcasm.consts.add_int(num as u64, ty, layout);
+                *op = Allocation::ConstUse { id };
+            }
+        }
+
+        node
+    }
 }
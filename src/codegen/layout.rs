@@ -0,0 +1,120 @@
+use crate::ir::TypeMetadata;
+
+/// The byte order of a target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endianness {
+    /// The least significant byte is stored first
+    Little,
+    /// The most significant byte is stored first
+    Big,
+}
+
+/// Describes how data is laid out in memory on a target
+///
+/// Example:
+/// ```rust
+/// use jacob::codegen::{Endianness, TargetArch};
+/// use jacob::ir::TypeMetadata;
+///
+/// let layout = TargetArch::X86.data_layout();
+///
+/// assert_eq!(layout.pointer_width(), 64);
+/// assert_eq!(layout.endianness(), Endianness::Little);
+/// assert_eq!(layout.align_of(TypeMetadata::Int64), 8);
+/// assert_eq!(layout.frame_size(3), 32);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DataLayout {
+    pointer_width: u32,
+    endianness: Endianness,
+    stack_align: usize,
+    largest_int: u32,
+}
+
+impl DataLayout {
+    /// The layout of 64 bit little endian targets which align the stack to 16 bytes
+    pub const LP64: DataLayout = DataLayout::new(64, Endianness::Little, 16, 64);
+
+    /// Creates a new data layout
+    ///
+    /// Args:
+    ///  - `pointer_width` the size of a pointer in bits
+    ///  - `endianness` the byte order
+    ///  - `stack_align` the alignment of the stack pointer in bytes
+    ///  - `largest_int` the size of the largest integer which fits into a register in bits
+    pub const fn new(
+        pointer_width: u32,
+        endianness: Endianness,
+        stack_align: usize,
+        largest_int: u32,
+    ) -> Self {
+        Self {
+            pointer_width,
+            endianness,
+            stack_align,
+            largest_int,
+        }
+    }
+
+    /// Returns the size of a pointer in bits
+    pub fn pointer_width(&self) -> u32 {
+        self.pointer_width
+    }
+
+    /// Returns the size of a pointer in bytes
+    pub fn pointer_size(&self) -> usize {
+        self.pointer_width as usize / 8
+    }
+
+    /// Returns the byte order
+    pub fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+
+    /// Returns the alignment of the stack pointer in bytes
+    pub fn stack_align(&self) -> usize {
+        self.stack_align
+    }
+
+    /// Returns the size of the largest integer which fits into a register in bits
+    pub fn largest_legal_int(&self) -> u32 {
+        self.largest_int
+    }
+
+    /// Returns if integers of the type fit into a register
+    pub fn is_legal(&self, ty: TypeMetadata) -> bool {
+        ty.bitwidth() <= self.largest_int
+    }
+
+    /// Returns the size of the type in bytes
+    pub fn size_of(&self, ty: TypeMetadata) -> usize {
+        ty.bitwidth().div_ceil(8) as usize
+    }
+
+    /// Returns the natural alignment of the type in bytes
+    pub fn align_of(&self, ty: TypeMetadata) -> usize {
+        self.size_of(ty).next_power_of_two()
+    }
+
+    /// Returns the size of a stack slot in bytes (every legal value fits into one)
+    pub fn slot_size(&self) -> usize {
+        self.largest_int as usize / 8
+    }
+
+    /// Returns the offset of the stack slot from the stack pointer
+    pub fn slot_offset(&self, slot: usize) -> usize {
+        slot * self.slot_size()
+    }
+
+    /// Returns the number of bytes a function with the given amount of stack slots
+    /// needs to reserve, rounded up to the stack alignment
+    pub fn frame_size(&self, slots: usize) -> usize {
+        (slots * self.slot_size()).next_multiple_of(self.stack_align)
+    }
+}
+
+impl Default for DataLayout {
+    fn default() -> Self {
+        DataLayout::LP64
+    }
+}
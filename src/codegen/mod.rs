@@ -5,45 +5,109 @@ pub mod asm;
 
 /// Trait implementations for printing an allocated ir node
 mod comment;
+/// Module wide constant pool
+pub mod constpool;
+/// Dwarf debug info generation
+pub mod debuginfo;
+/// Graphviz export of allocated ir
+pub mod dot;
 /// Resource dropping
 pub mod dropper;
 /// Instruction selection
 pub mod inst_selec;
+/// Memory layout of the targets
+pub mod layout;
 /// Register allocation
 pub mod regalloc;
 /// Target enum and target trait
 pub mod target;
 
 pub use asm::*;
+pub use constpool::*;
+pub use debuginfo::*;
+pub use dot::*;
 pub use dropper::*;
 pub use inst_selec::*;
+pub use layout::*;
 pub use regalloc::*;
 pub use target::*;
 
+pub use procmacro::{backend, patterns};
+
 /// The result of an compilation
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Compilation {
     /// The compilation result of the functions
     pub funcs: Vec<FuncAsm>,
     /// The target architecture
-    pub arch: TargetArch,
+    pub arch: Target,
+    /// Should dwarf debug info be emitted
+    pub debug_info: bool,
+    /// The constants of all functions
+    pub consts: ConstPool,
 }
 
 impl Compilation {
     /// Creates a new empty compilation result
-    pub fn new(arch: TargetArch) -> Self {
+    pub fn new(arch: impl Into<Target>) -> Self {
         Self {
             funcs: Vec::new(),
-            arch,
+            arch: arch.into(),
+            debug_info: false,
+            consts: ConstPool::new(),
         }
     }
     /// Adds the compilation result from a function
-    pub fn add(&mut self, asm: FuncAsm) {
+    ///
+    /// The constants of the function are moved into the constant pool of the compilation
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::codegen::{Compilation, FuncAsm, TargetArch};
+    /// use jacob::ir::visibility::Visibilty;
+    ///
+    /// let mut compilation = Compilation::new(TargetArch::X86);
+    ///
+    /// for name in ["a", "b"] {
+    ///     let mut func = FuncAsm::new(name.into(), &Visibilty::Public);
+    ///     func.add_const(&0x1122_3344_5566_7788u64.to_le_bytes(), 8);
+    ///     compilation.add(func);
+    /// }
+    ///
+    /// assert_eq!(compilation.consts.len(), 1);
+    ///
+    /// let asm = compilation.asm();
+    /// assert!(asm.contains(".section .rodata\n.p2align 3\n.Ljacob_const0:\n\t.quad 0x1122334455667788\n"));
+    /// ```
+    pub fn add(&mut self, mut asm: FuncAsm) {
+        let ids = self.consts.merge(&asm.consts);
+        asm.consts = ConstPool::new();
+
+        let remap = |insts: &mut Vec<AssemblyInst>| {
+            for op in insts.iter_mut().flat_map(|inst| inst.ops.iter_mut()) {
+                if let Allocation::ConstUse { id } = op {
+                    *id = ids[*id];
+                }
+            }
+        };
+
+        remap(&mut asm.insts);
+        for minst in &mut asm.meta_insts {
+            remap(&mut minst.insts);
+        }
+
         self.funcs.push(asm);
     }
 
     /// Returns a fully formated assembly code ready to be printed
     pub fn asm(&self) -> String {
-        self.arch.backend().print_compilation(self)
+        let backend = self.arch.backend();
+        let mut out = backend.print_compilation(self);
+
+        if self.debug_info {
+            out += &DebugInfo::new(backend).print(self);
+        }
+
+        out
     }
 }
@@ -1,8 +1,9 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::collections::HashMap;
 
 use crate::{
-    codegen::ArchBackend,
-    ir::{IrNode, IrOpcode, IrOperand, TypeMetadata},
+    codegen::{ArchBackend, allocated_ir_to_dot},
+    ir::{CallingConv, IrOpcode, IrOperand, NodeRef, SourceLoc, TypeMetadata},
+    opt::{Liveness, reachable, walk_nodes},
 };
 
 /// The resource to use for an allocation
@@ -55,6 +56,12 @@ impl Allocation {
         matches!(self, Allocation::Imm { .. })
     }
 
+    /// Returns if it's a ptr to a constant
+    #[inline]
+    pub fn is_const_use(&self) -> bool {
+        matches!(self, Allocation::ConstUse { .. })
+    }
+
     /// Returns the type of the allocation (not Register/Stack but Int64 for example)
     pub fn get_ty(&self) -> TypeMetadata {
         match self {
@@ -68,11 +75,25 @@ impl Allocation {
     /// Although this function seems very unneccessary (which it probably also is), removing
     /// this function would result in big changes to the `patterns!` proc macro
     #[inline]
-    pub(crate) fn is_any(&self) -> bool {
+    pub fn is_any(&self) -> bool {
         true
     }
 }
 
+/// A part of the code in which a value is stored in the allocation
+///
+/// `start` and `end` (exclusive) are indices of the allocated nodes or of the
+/// instructions they were lowered to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocRange {
+    /// The first node/instruction of the range
+    pub start: usize,
+    /// The node/instruction after the range
+    pub end: usize,
+    /// Where the value is stored
+    pub alloc: Allocation,
+}
+
 /// same as `src/ir/node.rs - IrNode` but with a allocated dest
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AllocatedIrNode {
@@ -81,119 +102,577 @@ pub struct AllocatedIrNode {
     pub(crate) has_out: bool,
     pub(crate) ty: Option<TypeMetadata>,
     pub(crate) alloc: Option<Allocation>,
+    pub(crate) loc: Option<SourceLoc>,
+    pub(crate) callee: Option<String>,
 }
+
+impl AllocatedIrNode {
+    /// Creates a new allocated node
+    pub fn new(
+        opcode: IrOpcode,
+        ops: Vec<Allocation>,
+        has_out: bool,
+        ty: Option<TypeMetadata>,
+        alloc: Option<Allocation>,
+    ) -> Self {
+        Self {
+            opcode,
+            ops,
+            has_out,
+            ty,
+            alloc,
+            loc: None,
+            callee: None,
+        }
+    }
+
+    /// Sets the name of the called function
+    pub fn with_callee(mut self, callee: &str) -> Self {
+        self.callee = Some(callee.to_owned());
+        self
+    }
+
+    /// Returns the opcode of the node
+    pub fn opcode(&self) -> IrOpcode {
+        self.opcode
+    }
+
+    /// Returns the allocated operands
+    pub fn ops(&self) -> &[Allocation] {
+        &self.ops
+    }
+
+    /// Returns if the node produces a value
+    pub fn has_out(&self) -> bool {
+        self.has_out
+    }
+
+    /// Returns the type of the node
+    pub fn ty(&self) -> Option<TypeMetadata> {
+        self.ty
+    }
+
+    /// Returns the allocation of the output
+    pub fn alloc(&self) -> Option<Allocation> {
+        self.alloc
+    }
+
+    /// Returns the name of the called function (if the node is a call)
+    pub fn callee(&self) -> Option<&str> {
+        self.callee.as_deref()
+    }
+}
+
+/// How the register allocator assigns the outputs of nodes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum RegAllocStrategy {
+    /// Every output gets a resource which isn't used by the operands
+    #[default]
+    Fast,
+    /// Operands which die at the node are freed before the output is allocated,
+    /// so the output can reuse their register (which suits two address instructions)
+    ReuseOperands,
+}
+
 /// Helper structure for register allocation
 pub struct RegAlloc<'a> {
-    args: Vec<TypeMetadata>,
-
+    args: Vec<Allocation>,
+    callconv: CallingConv,
+    callconvs: Option<&'a HashMap<String, CallingConv>>,
+    strategy: RegAllocStrategy,
+    coalescing: bool,
     allocated_ir: Vec<AllocatedIrNode>,
     free_regs: Vec<Allocation>,
     freed_mem: Vec<Allocation>,
 
+    liveness: Liveness,
+    calls: Vec<(usize, CallingConv)>,
+    arg_homes: HashMap<usize, Allocation>,
+    home_copies: HashMap<usize, usize>,
+    incoming: Vec<(usize, usize)>,
+
     max_stack_poses_used: usize,
-    used_callee_saved_regs: bool, // ToDo
+    used_callee_saved_regs: Vec<Allocation>,
+    saved_regs: Vec<(Allocation, Allocation)>,
+    link_slot: Option<Allocation>,
+    frame_size: usize,
 
     back: &'a dyn ArchBackend,
 }
 
 impl<'a> RegAlloc<'a> {
-    /// Creates a register allocator
+    /// Creates a register allocator for a function using the default calling convention
     pub fn new(args: Vec<TypeMetadata>, backend: &'a dyn ArchBackend) -> Self {
-        // ToDo: remove args from free regs
+        RegAlloc::with_callconv(args, CallingConv::C, backend)
+    }
+
+    /// Creates a register allocator for a function using the given calling convention
+    pub fn with_callconv(
+        args: Vec<TypeMetadata>,
+        callconv: CallingConv,
+        backend: &'a dyn ArchBackend,
+    ) -> Self {
+        let args: Vec<Allocation> = args
+            .iter()
+            .enumerate()
+            .map(|(num, ty)| backend.callconv_argpos_for(callconv, num, *ty))
+            .collect();
+
+        let mut free_regs: Vec<Allocation> = backend
+            .grps()
+            .iter()
+            .map(|x| Allocation::Register {
+                id: x.id(),
+                ty: x.ty(),
+            })
+            .filter(|reg| !args.contains(reg))
+            .rev()
+            .collect();
+
+        // registers are popped from the back, so the caller saved ones are handed out first
+        free_regs.sort_by_key(|reg| match reg {
+            Allocation::Register { id, .. } => !backend.callconv_is_callee_saved(callconv, *id),
+            _ => false,
+        });
+
         Self {
             args,
+            callconv,
+            callconvs: None,
+            strategy: RegAllocStrategy::default(),
+            coalescing: false,
             allocated_ir: Vec::new(),
 
-            free_regs: backend
-                .grps()
-                .iter()
-                .map(|x| Allocation::Register {
-                    id: x.id(),
-                    ty: x.ty(),
-                })
-                .rev()
-                .collect(),
+            free_regs,
             freed_mem: Vec::new(),
+            liveness: Liveness::default(),
+            calls: Vec::new(),
+            arg_homes: HashMap::new(),
+            home_copies: HashMap::new(),
+            incoming: Vec::new(),
             max_stack_poses_used: 0,
-            used_callee_saved_regs: false,
+            used_callee_saved_regs: Vec::new(),
+            saved_regs: Vec::new(),
+            link_slot: None,
+            frame_size: 0,
             back: backend,
         }
     }
 
+    /// Sets the calling conventions of the functions which can be called
+    ///
+    /// Calls to functions which aren't in the map use the default calling convention
+    pub fn with_callconvs(mut self, callconvs: &'a HashMap<String, CallingConv>) -> Self {
+        self.callconvs = Some(callconvs);
+        self
+    }
+
+    /// Sets the strategy used to assign the outputs
+    pub fn with_strategy(mut self, strategy: RegAllocStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Sets if copies are coalesced
+    ///
+    /// A copy whose operand isn't used afterwards (so their live ranges don't interfere)
+    /// gets the resource of the operand and isn't emitted at all
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::codegen::{Dropper, RegAlloc, TargetArch};
+    /// use jacob::ir::{Function, TypeMetadata};
+    /// use jacob::opt::AnalysisManager;
+    ///
+    /// let mut func = Function::new("double");
+    /// let x = func.add_arg(TypeMetadata::Int64);
+    /// let sum = func.add(&x, &x);
+    /// let copy = func.copy(&sum);
+    /// func.ret(&copy);
+    ///
+    /// let backend = TargetArch::X86.backend();
+    ///
+    /// let mut dropper = Dropper::new(&func);
+    /// dropper.run(&mut AnalysisManager::new());
+    ///
+    /// let mut regalloc = RegAlloc::new(func.args().clone(), &*backend).with_coalescing(true);
+    /// regalloc.run(dropper.get_ir());
+    ///
+    /// let ir = regalloc.get_ir();
+    /// assert_eq!(ir.len(), 2);
+    /// assert_eq!(ir[0].alloc(), Some(ir[1].ops()[0]));
+    /// ```
+    pub fn with_coalescing(mut self, coalescing: bool) -> Self {
+        self.coalescing = coalescing;
+        self
+    }
+
     /// Runs the register allocator
-    pub fn run(&mut self, ir: &Vec<IrOperand>) {
+    ///
+    /// Values which are live across a call are kept in registers which the called function
+    /// preserves (or on the stack), arguments which are passed in other registers get copied
+    /// there on entry. Which registers survive a call depends on the calling convention of
+    /// the callee, not on the one of the function itself
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::codegen::{Allocation, ArchBackend, Dropper, RegAlloc, TargetArch};
+    /// use jacob::ir::{CallingConv, Function, IrOpcode, TypeMetadata};
+    /// use jacob::opt::AnalysisManager;
+    ///
+    /// let mut func = Function::new("g");
+    /// let x = func.add_arg(TypeMetadata::Int64);
+    /// let y = func.add_arg(TypeMetadata::Int64);
+    /// let sum = func.add(&x, &y);
+    /// let called = func.call("h", &[sum.clone()], Some(TypeMetadata::Int64));
+    /// let tmp = func.add(&called, &sum);
+    /// let out = func.add(&tmp, &x);
+    /// func.ret(&out);
+    ///
+    /// let backend = TargetArch::X86.backend();
+    ///
+    /// let mut dropper = Dropper::new(&func);
+    /// dropper.run(&mut AnalysisManager::new());
+    ///
+    /// let mut regalloc = RegAlloc::new(func.args().clone(), &*backend);
+    /// regalloc.run(dropper.get_ir());
+    ///
+    /// let survives_call = |alloc: Allocation| match alloc {
+    ///     Allocation::Register { id, .. } => backend.callconv_is_callee_saved(CallingConv::C, id),
+    ///     _ => true,
+    /// };
+    ///
+    /// let ir = regalloc.get_ir();
+    /// let call = ir.iter().position(|node| node.opcode() == IrOpcode::Call).unwrap();
+    ///
+    /// // the sum and x are used after the call
+    /// assert!(survives_call(ir[call + 1].ops()[1]));
+    /// assert!(survives_call(ir[call + 2].ops()[1]));
+    ///
+    /// // a preserve most function has more callee saved registers, but h may still clobber them
+    /// let mut regalloc =
+    ///     RegAlloc::with_callconv(func.args().clone(), CallingConv::PreserveMost, &*backend);
+    /// regalloc.run(dropper.get_ir());
+    ///
+    /// let ir = regalloc.get_ir();
+    /// let call = ir.iter().position(|node| node.opcode() == IrOpcode::Call).unwrap();
+    ///
+    /// assert!(survives_call(ir[call + 1].ops()[1]));
+    /// assert!(survives_call(ir[call + 2].ops()[1]));
+    /// ```
+    pub fn run(&mut self, ir: &[IrOperand]) {
         let mut allocs = HashMap::new();
+        let ir = reachable(ir);
+
+        let nodes = walk_nodes(ir);
+        self.liveness = Liveness::of_ir(ir);
+        self.calls = nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.borrow().is_call())
+            .map(|(pos, node)| (pos, self.callee_conv(node.borrow().callee.as_deref())))
+            .collect();
+
+        // the stack arguments of calls are stored at the bottom of the frame
+        for node in nodes.iter().filter(|node| node.borrow().is_call()) {
+            let node = node.borrow();
+            let conv = self.callee_conv(node.callee.as_deref());
+            let args: Vec<TypeMetadata> = node.ops.iter().map(|op| op.get_ty()).collect();
+
+            self.max_stack_poses_used = self
+                .max_stack_poses_used
+                .max(self.back.callconv_call_slots(conv, &args));
+        }
+
+        self.save_call_clobbers();
+        self.home_args(&nodes);
 
         for op in ir {
-            if let IrOperand::Out(node) = op {
-                self.make_node(op.hash_u64(), node, &mut allocs);
+            if let IrOperand::Out(node) = op
+                && !allocs.contains_key(&node.id())
+            {
+                self.make_node(node, &mut allocs);
             }
         }
+
+        self.finish_frame();
     }
 
-    fn make_node(
-        &mut self,
-        operand_hash: u64,
-        node: &Rc<RefCell<IrNode>>,
-        allocs: &mut HashMap<u64, Allocation>,
-    ) {
-        let node = node.borrow();
+    /// Reserves the slots in which the used callee saved registers are preserved
+    /// and computes the size of the stack frame
+    fn finish_frame(&mut self) {
+        for reg in self.used_callee_saved_regs.clone() {
+            let slot = Allocation::Stack {
+                slot: self.max_stack_poses_used,
+                ty: reg.get_ty(),
+            };
+            self.max_stack_poses_used += 1;
 
-        let mut alloc = None;
-        let mut ops = Vec::new();
+            self.saved_regs.push((reg, slot));
+        }
+
+        // calls overwrite the link register, so the own return address is preserved
+        if !self.calls.is_empty() && self.back.call_uses_link_reg() {
+            self.link_slot = Some(Allocation::Stack {
+                slot: self.max_stack_poses_used,
+                ty: TypeMetadata::Int64,
+            });
+            self.max_stack_poses_used += 1;
+        }
 
-        if node.has_out {
-            if let std::collections::hash_map::Entry::Vacant(e) = allocs.entry(operand_hash) {
-                let new_alloc = self.alloc(node.ty);
-                e.insert(new_alloc);
+        // functions which call others need an aligned stack even if they don't use slots
+        if self.max_stack_poses_used != 0 || !self.calls.is_empty() {
+            self.frame_size = self.back.frame_size(self.max_stack_poses_used);
+        }
 
-                alloc = Some(new_alloc);
-            } else {
-                alloc = allocs.get(&operand_hash).copied();
+        // stack arguments are in the frame of the caller (above the return address)
+        let above =
+            (self.frame_size + self.back.call_pushed_bytes()) / self.back.data_layout().slot_size();
+        let incoming = |alloc: &mut Allocation| {
+            if let Allocation::Stack { slot, .. } = alloc {
+                *slot += above;
             }
+        };
+
+        for (node, op) in &self.incoming {
+            incoming(&mut self.allocated_ir[*node].ops[*op]);
         }
+        self.args.iter_mut().for_each(incoming);
+    }
+
+    /// Marks the registers which the function has to preserve but the calls overwrite
+    /// (because the callee doesn't preserve them or they pass the arguments and the result)
+    fn save_call_clobbers(&mut self) {
+        for (_, conv) in self.calls.clone() {
+            let args = self.back.callconv_arg_regs(conv).unwrap_or_default();
 
-        for op in &node.ops {
-            if op.is_drop() {
-                if let Some(alloc) = allocs.get(&op.hash_u64()) {
-                    println!("{:?}", operand_hash == op.hash_u64());
-                    ops.push(*alloc);
-                    self.free(*alloc);
-                } else {
-                    let op = op.force_op();
-                    if op.is_out() {
-                        self.make_node(op.hash_u64(), op.force_node(), allocs);
-                        ops.push(
-                            *allocs
-                                .get(&op.hash_u64())
-                                .expect("ToDo: Insert error message here"),
-                        );
-                    } else if op.is_arg() {
-                        let (num, ty) = op.force_arg();
-                        self.free_arg(num, ty);
-                        ops.push(self.pos_for_arg(num, ty));
+            for reg in self.back.grps() {
+                let reg = reg.alloc();
+                let written =
+                    reg == self.back.ret_reg() || args.iter().any(|arg| arg.alloc() == reg);
+
+                if self.needs_save(reg)
+                    && (written || !self.survives(reg, &[conv]))
+                    && !self.used_callee_saved_regs.contains(&reg)
+                {
+                    self.used_callee_saved_regs.push(reg);
+                }
+            }
+        }
+    }
+
+    /// Copies the arguments which are live across a call out of caller saved registers
+    fn home_args(&mut self, nodes: &[NodeRef]) {
+        let mut last_uses = HashMap::new();
+
+        for (pos, node) in nodes.iter().enumerate() {
+            for op in node.borrow().ops() {
+                if let IrOperand::Arg { num, .. } = op.strip_drop() {
+                    last_uses.insert(*num, pos);
+                }
+            }
+        }
+
+        for (num, arg) in self.args.clone().into_iter().enumerate() {
+            let Some(last_use) = last_uses.get(&num) else {
+                continue;
+            };
+
+            let crossed: Vec<CallingConv> = self
+                .calls
+                .iter()
+                .filter(|(call, _)| call < last_use)
+                .map(|(_, conv)| *conv)
+                .collect();
+
+            if self.survives(arg, &crossed) {
+                continue;
+            }
+
+            let home = self.alloc(Some(arg.get_ty()), &crossed);
+            self.arg_homes.insert(num, home);
+            self.home_copies.insert(num, self.allocated_ir.len());
+
+            self.allocated_ir.push(AllocatedIrNode {
+                opcode: IrOpcode::Copy,
+                ops: vec![arg],
+                has_out: true,
+                ty: Some(arg.get_ty()),
+                alloc: Some(home),
+                loc: None,
+                callee: None,
+            });
+        }
+    }
+
+    /// Returns the calling convention of the function called by the node
+    fn callee_conv(&self, callee: Option<&str>) -> CallingConv {
+        callee
+            .and_then(|callee| self.callconvs?.get(callee))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns the calling conventions of the calls the output of the node is live across
+    fn crossed_calls(&self, node: &NodeRef) -> Vec<CallingConv> {
+        let Some((def, last_use)) = self.liveness.range(node) else {
+            return Vec::new();
+        };
+
+        self.calls
+            .iter()
+            .filter(|(call, _)| def < *call && *call < last_use)
+            .map(|(_, conv)| *conv)
+            .collect()
+    }
+
+    /// Returns if the resource keeps its value during calls of functions with the conventions
+    fn survives(&self, alloc: Allocation, convs: &[CallingConv]) -> bool {
+        match alloc {
+            Allocation::Register { id, .. } => convs
+                .iter()
+                .all(|conv| self.back.callconv_is_callee_saved(*conv, id)),
+            _ => true,
+        }
+    }
+
+    /// Returns if the function has to preserve the resource when it uses it
+    fn needs_save(&self, alloc: Allocation) -> bool {
+        match alloc {
+            Allocation::Register { id, .. } => {
+                self.back.callconv_is_callee_saved(self.callconv, id)
+            }
+            _ => false,
+        }
+    }
+
+    /// Allocates the node (and the nodes it depends on which weren't allocated yet)
+    ///
+    /// `allocs` maps the ids of the allocated nodes to their output
+    fn make_node(&mut self, node: &NodeRef, allocs: &mut HashMap<usize, Option<Allocation>>) {
+        let id = node.id();
+        let crossed = self.crossed_calls(node);
+        let node = node.borrow();
+
+        let mut ops = Vec::new();
+        let mut incoming = Vec::new();
+        let mut temps = Vec::new();
+
+        for (index, op) in node.ops.iter().enumerate() {
+            let alloc = match op.strip_drop() {
+                IrOperand::Out(used) => {
+                    if !allocs.contains_key(&used.id()) {
+                        self.make_node(used, allocs);
+                    }
+
+                    allocs[&used.id()].expect("The operand does not produce a value")
+                }
+                IrOperand::Arg { num, ty } => {
+                    let pos = self.pos_for_arg(*num, *ty);
+                    if pos.is_mem() && !self.arg_homes.contains_key(num) {
+                        incoming.push(index);
+                    }
+                    pos
+                }
+                IrOperand::ConstNum { num, ty } => {
+                    let imm = Allocation::Imm { num: *num, ty: *ty };
+
+                    if node.opcode == IrOpcode::Copy || self.back.is_legal_imm(*num, *ty) {
+                        imm
                     } else {
-                        todo!("{op:?}")
+                        // the number is loaded (from the constant pool) into a temporary register
+                        let tmp = self.alloc(Some(*ty), &[]);
+                        self.allocated_ir.push(AllocatedIrNode {
+                            opcode: IrOpcode::Copy,
+                            ops: vec![imm],
+                            has_out: true,
+                            ty: Some(*ty),
+                            alloc: Some(tmp),
+                            loc: node.loc.clone(),
+                            callee: None,
+                        });
+                        temps.push(tmp);
+                        tmp
                     }
                 }
-            } else {
-                let (num, ty) = op.force_arg();
-                ops.push(self.pos_for_arg(num, ty))
+                IrOperand::Drop(_) => unreachable!("drops are stripped"),
+            };
+
+            ops.push(alloc);
+        }
+
+        if self.coalescing
+            && node.opcode == IrOpcode::Copy
+            && node.ops[0].is_drop()
+            && !ops[0].is_imm()
+            && self.survives(ops[0], &crossed)
+        {
+            allocs.insert(id, Some(ops[0]));
+            return;
+        }
+
+        let reuse = self.strategy == RegAllocStrategy::ReuseOperands;
+
+        if !reuse {
+            let alloc = node.has_out.then(|| self.alloc(node.ty, &crossed));
+            allocs.insert(id, alloc);
+        }
+
+        for (op, alloc) in node.ops.iter().zip(&ops) {
+            if op.is_drop() && !alloc.is_imm() {
+                self.free(*alloc);
             }
         }
 
+        for tmp in temps {
+            self.free(tmp);
+        }
+
+        if reuse {
+            let alloc = node.has_out.then(|| self.alloc(node.ty, &crossed));
+            allocs.insert(id, alloc);
+        }
+
+        let alloc = allocs[&id];
+
+        let index = self.allocated_ir.len();
+        self.incoming
+            .extend(incoming.into_iter().map(|op| (index, op)));
+
         self.allocated_ir.push(AllocatedIrNode {
             opcode: node.opcode,
             ops,
             has_out: node.has_out,
             ty: node.ty,
             alloc,
+            loc: node.loc.clone(),
+            callee: node.callee.clone(),
         });
     }
 
     /// Allocates a resource
-    fn alloc(&mut self, _ty: Option<TypeMetadata>) -> Allocation {
-        if let Some(reg) = self.free_regs.pop() {
+    ///
+    /// If the value is live across calls, only resources which the called functions
+    /// (with the given conventions) preserve are used
+    fn alloc(&mut self, _ty: Option<TypeMetadata>, crossed: &[CallingConv]) -> Allocation {
+        // registers which the function doesn't have to preserve itself are preferred
+        let reg = self
+            .free_regs
+            .iter()
+            .rposition(|reg| self.survives(*reg, crossed) && !self.needs_save(*reg))
+            .or_else(|| {
+                self.free_regs
+                    .iter()
+                    .rposition(|reg| self.survives(*reg, crossed))
+            });
+
+        if let Some(reg) = reg {
+            let reg = self.free_regs.remove(reg);
+
+            if self.needs_save(reg) && !self.used_callee_saved_regs.contains(&reg) {
+                self.used_callee_saved_regs.push(reg);
+            }
+
             return reg;
         }
         if let Some(freed) = self.freed_mem.pop() {
@@ -222,17 +701,220 @@ impl<'a> RegAlloc<'a> {
     /// Returns the position for the given argument
     #[inline]
     fn pos_for_arg(&self, num: usize, ty: TypeMetadata) -> Allocation {
-        self.back.callconv_argpos(num, ty)
+        if let Some(home) = self.arg_homes.get(&num) {
+            return *home;
+        }
+
+        self.back.callconv_argpos_for(self.callconv, num, ty)
     }
 
-    /// Frees the given argument
-    #[inline]
-    fn free_arg(&mut self, num: usize, ty: TypeMetadata) {
-        self.free(self.pos_for_arg(num, ty));
+    /// Returns the allocations of the arguments on entry of the function
+    pub fn args(&self) -> &Vec<Allocation> {
+        &self.args
+    }
+
+    /// Returns for every argument the ranges of the allocated ir in which it is stored
+    /// in its entry allocation or in the copy which keeps it alive across calls
+    ///
+    /// A range ends at the first node which overwrites the allocation, so the ranges
+    /// stay correct after the allocation is reused for other values
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::codegen::{Dropper, LocRange, Reg, RegAlloc, TargetArch};
+    /// use jacob::ir::{Function, TypeMetadata};
+    /// use jacob::opt::AnalysisManager;
+    /// use jacob::x86::{RBX, RDI};
+    ///
+    /// let mut func = Function::new("f");
+    /// let x = func.add_arg(TypeMetadata::Int64);
+    /// let called = func.call("g", &[], Some(TypeMetadata::Int64));
+    /// let out = func.add(&called, &x);
+    /// func.ret(&out);
+    ///
+    /// let backend = TargetArch::X86.backend();
+    ///
+    /// let mut dropper = Dropper::new(&func);
+    /// dropper.run(&mut AnalysisManager::new());
+    ///
+    /// let mut regalloc = RegAlloc::new(func.args().clone(), &*backend);
+    /// regalloc.run(dropper.get_ir());
+    ///
+    /// // x is copied out of rdi before the call, which overwrites rdi
+    /// let ranges = regalloc.arg_ranges();
+    /// assert_eq!(ranges[0], vec![
+    ///     LocRange { start: 0, end: 1, alloc: RDI.alloc() },
+    ///     LocRange { start: 1, end: regalloc.get_ir().len(), alloc: RBX.alloc() },
+    /// ]);
+    /// ```
+    pub fn arg_ranges(&self) -> Vec<Vec<LocRange>> {
+        let mut out = Vec::new();
+
+        for (num, entry) in self.args.iter().enumerate() {
+            let home = self
+                .home_copies
+                .get(&num)
+                .map(|copy| (copy + 1, self.arg_homes[&num]));
+
+            let mut end = self.overwritten_at(*entry, 0);
+            if let Some((start, _)) = home {
+                end = end.min(start);
+            }
+
+            let mut ranges = vec![LocRange {
+                start: 0,
+                end,
+                alloc: *entry,
+            }];
+
+            if let Some((start, alloc)) = home {
+                ranges.push(LocRange {
+                    start,
+                    end: self.overwritten_at(alloc, start),
+                    alloc,
+                });
+            }
+
+            ranges.retain(|range| range.start < range.end);
+            out.push(ranges);
+        }
+
+        out
+    }
+
+    /// Returns the index of the first node (from `start` on) which overwrites the allocation
+    fn overwritten_at(&self, alloc: Allocation, start: usize) -> usize {
+        (start..self.allocated_ir.len())
+            .find(|pos| self.overwrites(&self.allocated_ir[*pos], alloc))
+            .unwrap_or(self.allocated_ir.len())
+    }
+
+    /// Returns if executing the node changes the value stored in the allocation
+    fn overwrites(&self, node: &AllocatedIrNode, alloc: Allocation) -> bool {
+        if node.alloc == Some(alloc) {
+            return true;
+        }
+
+        if node.opcode != IrOpcode::Call || !alloc.is_gr() {
+            return false;
+        }
+
+        let conv = self.callee_conv(node.callee.as_deref());
+        let args = self.back.callconv_arg_regs(conv).unwrap_or_default();
+
+        alloc == self.back.ret_reg()
+            || args.iter().any(|arg| arg.alloc() == alloc)
+            || !self.survives(alloc, &[conv])
+    }
+
+    /// Returns the number of stack slots which were used (including the preserved registers)
+    pub fn stack_slots(&self) -> usize {
+        self.max_stack_poses_used
+    }
+
+    /// Returns the callee saved registers which were allocated
+    pub fn used_callee_saved_regs(&self) -> &[Allocation] {
+        &self.used_callee_saved_regs
+    }
+
+    /// Returns the used callee saved registers and the stack slots they are preserved in
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::codegen::{Dropper, RegAlloc, TargetArch};
+    /// use jacob::ir::{Function, TypeMetadata};
+    /// use jacob::opt::AnalysisManager;
+    ///
+    /// let mut func = Function::new("g");
+    /// let x = func.add_arg(TypeMetadata::Int64);
+    /// let called = func.call("h", &[], Some(TypeMetadata::Int64));
+    /// let out = func.add(&called, &x);
+    /// func.ret(&out);
+    ///
+    /// let backend = TargetArch::X86.backend();
+    ///
+    /// let mut dropper = Dropper::new(&func);
+    /// dropper.run(&mut AnalysisManager::new());
+    ///
+    /// let mut regalloc = RegAlloc::new(func.args().clone(), &*backend);
+    /// regalloc.run(dropper.get_ir());
+    ///
+    /// // x is kept in rbx during the call, so rbx is preserved
+    /// assert_eq!(regalloc.saved_regs().len(), 1);
+    /// // the return address and the frame keep the stack aligned to 16 bytes for the call
+    /// assert_eq!(regalloc.frame_size(), 8);
+    /// ```
+    pub fn saved_regs(&self) -> &[(Allocation, Allocation)] {
+        &self.saved_regs
+    }
+
+    /// Returns the slot in which the link register is preserved
+    ///
+    /// It is only used on targets whose calls store the return address in a register
+    /// and only if the function calls others
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::codegen::{Dropper, RegAlloc, TargetArch};
+    /// use jacob::ir::{Function, TypeMetadata};
+    /// use jacob::opt::AnalysisManager;
+    ///
+    /// let mut func = Function::new("g");
+    /// let called = func.call("h", &[], Some(TypeMetadata::Int64));
+    /// func.ret(&called);
+    ///
+    /// let mut dropper = Dropper::new(&func);
+    /// dropper.run(&mut AnalysisManager::new());
+    ///
+    /// for (arch, saved) in [(TargetArch::X86, false), (TargetArch::Aarch64, true)] {
+    ///     let backend = arch.backend();
+    ///     let mut regalloc = RegAlloc::new(Vec::new(), &*backend);
+    ///     regalloc.run(dropper.get_ir());
+    ///
+    ///     assert_eq!(regalloc.link_slot().is_some(), saved);
+    /// }
+    /// ```
+    pub fn link_slot(&self) -> Option<Allocation> {
+        self.link_slot
+    }
+
+    /// Returns the size of the stack frame in bytes (including the preserved registers)
+    pub fn frame_size(&self) -> usize {
+        self.frame_size
     }
 
     /// Returns the new and allocated ir
     pub fn get_ir(&self) -> &Vec<AllocatedIrNode> {
         &self.allocated_ir
     }
+
+    /// Renders the allocated ir in the graphviz dot format
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::codegen::{Dropper, RegAlloc, TargetArch};
+    /// use jacob::ir::{Function, TypeMetadata};
+    /// use jacob::opt::AnalysisManager;
+    ///
+    /// let mut func = Function::new("add");
+    /// let x = func.add_arg(TypeMetadata::Int64);
+    /// let y = func.add_arg(TypeMetadata::Int64);
+    /// let out = func.add(&x, &y);
+    /// func.ret(&out);
+    ///
+    /// let backend = TargetArch::X86.backend();
+    ///
+    /// let mut dropper = Dropper::new(&func);
+    /// dropper.run(&mut AnalysisManager::new());
+    ///
+    /// let mut regalloc = RegAlloc::new(func.args().clone(), &*backend);
+    /// regalloc.run(dropper.get_ir());
+    ///
+    /// let dot = regalloc.to_dot("add");
+    /// assert!(dot.starts_with("digraph \"add\" {"));
+    /// assert!(dot.contains("entry -> n0"));
+    /// ```
+    pub fn to_dot(&self, name: &str) -> String {
+        allocated_ir_to_dot(name, &self.allocated_ir)
+    }
 }
@@ -1,8 +1,17 @@
-use std::any::Any;
+use std::{any::Any, fmt::Debug, sync::Arc};
 
 use crate::{
-    codegen::{AllocatedIrNode, Allocation, AssemblyInst, Compilation, Constant},
-    ir::{InstrincSettings, InstrincType, IrOpcode, TypeMetadata, visibility::Visibilty},
+    codegen::{
+        AllocatedIrNode, Allocation, AssemblyInst, Compilation, Constant, DataLayout, Endianness,
+        FuncAsm,
+        constpool::const_label,
+        debuginfo::{func_end_label, inst_label},
+    },
+    ir::{
+        AttributeList, CallingConv, FunctionAttribute, InstrincSettings, InstrincType, IrOpcode,
+        SourceLoc, TypeMetadata,
+        visibility::{SymbolVisibility, Visibilty},
+    },
 };
 
 /// The target architecture
@@ -25,11 +34,177 @@ impl TargetArch {
             TargetArch::Riscv64 => Box::new(crate::riscv64::Riscv64Backend {}),
         }
     }
+
+    /// Returns the data layout of the architecture
+    pub fn data_layout(&self) -> DataLayout {
+        self.backend().data_layout()
+    }
+}
+
+/// A shared handle to the backend to compile for
+///
+/// Every `TargetArch` converts into a `Target`, backends defined outside of this
+/// crate (using the `backend!` and `patterns!` macros) are wrapped with `Target::new`
+///
+/// Example:
+/// ```rust
+/// use jacob::codegen::{
+///     AsmPrinter, CallConvInfos, CallLowering, DataLayout, DebugInfos, InstrincLowering, Reg,
+///     Target, backend, patterns,
+/// };
+/// use jacob::ir::{CallingConv, Function, Module, TypeMetadata};
+///
+/// const LAYOUT: DataLayout = DataLayout::LP64;
+///
+/// backend! {
+///     name: Dsp,
+///     ret_reg: R0,
+///     stack_reg: SP,
+///     data_layout: LAYOUT,
+///     caller_saved: [ R0, R1, R2 ],
+///     callee_saved: [ R3 ],
+///     gprs: [ R0, R1, R2, R3 ],
+///     arg_reg_map: {
+///         0 -> R1,
+///         1 -> R2,
+///     },
+///     stack_off: 0,
+/// }
+///
+/// impl jacob::codegen::BackendInst for DspBackend {
+///     patterns! {
+///         Add(Gr, Gr) -> Gr {
+///             asm: add (out, in1, in2)
+///         }
+///         Ret(Gr) {
+///             condition: in1 == R0.alloc()
+///             asm: ret()
+///         }
+///         Ret(Gr) {
+///             condition: in1 != R0.alloc()
+///             asm: mov(R0.alloc(), in1)
+///             asm: ret()
+///         }
+///     }
+/// }
+///
+/// impl AsmPrinter for DspBackend {
+///     fn print_op(&self, op: &jacob::codegen::Allocation) -> String {
+///         match op {
+///             jacob::codegen::Allocation::Register { id, ty } => self.print_reg(id, ty),
+///             other => todo!("{other:?}"),
+///         }
+///     }
+///
+///     fn print_reg(&self, num: &usize, _ty: &TypeMetadata) -> String {
+///         format!("r{num}")
+///     }
+/// }
+///
+/// impl InstrincLowering for DspBackend {}
+///
+/// impl DebugInfos for DspBackend {
+///     fn dwarf_reg(&self, id: usize) -> Option<u16> {
+///         Some(id as u16)
+///     }
+///
+///     fn dwarf_stack_reg(&self) -> u16 {
+///         SP.id() as u16
+///     }
+/// }
+///
+/// impl CallConvInfos for DspBackend {
+///     fn callconv_arg_regs(&self, conv: CallingConv) -> Option<Vec<Box<dyn Reg>>> {
+///         (conv == CallingConv::C).then(|| vec![Box::new(R1) as Box<dyn Reg>, Box::new(R2)])
+///     }
+///
+///     fn callconv_callee_saved(&self, conv: CallingConv) -> Option<Vec<Box<dyn Reg>>> {
+///         (conv == CallingConv::C).then(|| vec![Box::new(R3) as Box<dyn Reg>])
+///     }
+/// }
+///
+/// impl CallLowering for DspBackend {
+///     fn call_opcode(&self) -> &'static str {
+///         "jsr"
+///     }
+/// }
+///
+/// let mut func = Function::new("add");
+/// let x = func.add_arg(TypeMetadata::Int64);
+/// let y = func.add_arg(TypeMetadata::Int64);
+/// let out = func.add(&x, &y);
+/// func.ret(&out);
+///
+/// let mut module = Module::new();
+/// module.add_func(func);
+///
+/// let asm = module.compile(Target::new(DspBackend {}), false).asm();
+/// assert!(asm.contains("add:\n\tadd r0, r1, r2\n\tret \n"));
+/// ```
+#[derive(Clone)]
+pub struct Target(Arc<dyn ArchBackend>);
+
+impl Target {
+    /// Creates a new target for the backend
+    pub fn new<T: ArchBackend>(backend: T) -> Self {
+        Self(Arc::new(backend))
+    }
+
+    /// Returns the backend of the target
+    pub fn backend(&self) -> &dyn ArchBackend {
+        &*self.0
+    }
+
+    /// Returns the name of the target
+    pub fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
+    /// Returns the data layout of the target
+    pub fn data_layout(&self) -> DataLayout {
+        self.0.data_layout()
+    }
+}
+
+impl From<TargetArch> for Target {
+    fn from(arch: TargetArch) -> Self {
+        Target(Arc::from(arch.backend()))
+    }
+}
+
+impl From<Arc<dyn ArchBackend>> for Target {
+    fn from(backend: Arc<dyn ArchBackend>) -> Self {
+        Target(backend)
+    }
+}
+
+impl Debug for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Target").field(&self.name()).finish()
+    }
 }
 
+impl PartialEq for Target {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0) || self.name() == other.name()
+    }
+}
+
+impl Eq for Target {}
+
 /// The trait to implement when defining the backend for a custom architecture
 pub trait ArchBackend:
-    Any + ArchInfos + BackendInst + AsmPrinter + BackendDecompiler + InstrincLowering
+    Any
+    + Send
+    + Sync
+    + ArchInfos
+    + BackendInst
+    + AsmPrinter
+    + BackendDecompiler
+    + InstrincLowering
+    + DebugInfos
+    + CallConvInfos
+    + CallLowering
 {
 }
 
@@ -55,6 +230,300 @@ pub trait ArchInfos {
 
     /// Returns the stack pointer
     fn get_stack_ptr(&self) -> Allocation;
+
+    /// Returns the data layout of the architecture
+    fn data_layout(&self) -> DataLayout;
+}
+
+/// The trait to implement for describing the calling conventions the architecture supports
+pub trait CallConvInfos: ArchInfos {
+    /// Returns the registers which are used for passing arguments in the calling convention
+    /// (or `None` if the convention is not supported by the architecture)
+    fn callconv_arg_regs(&self, conv: CallingConv) -> Option<Vec<Box<dyn Reg>>>;
+
+    /// Returns the registers which need to be preserved by the callee in the calling convention
+    /// (or `None` if the convention is not supported by the architecture)
+    fn callconv_callee_saved(&self, conv: CallingConv) -> Option<Vec<Box<dyn Reg>>>;
+
+    /// Returns if the architecture supports the calling convention
+    fn supports_callconv(&self, conv: CallingConv) -> bool {
+        self.callconv_arg_regs(conv).is_some()
+    }
+
+    /// Returns the number of bytes the caller reserves for the callee below the stack arguments
+    /// (like the shadow space of Win64)
+    fn callconv_shadow_space(&self, _conv: CallingConv) -> usize {
+        0
+    }
+
+    /// Returns the position for an argument in the calling convention
+    ///
+    /// Arguments which don't fit into registers are passed on the stack after the shadow space,
+    /// their slots are relative to the stack pointer at the call
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::codegen::{Allocation, CallConvInfos};
+    /// use jacob::ir::{CallingConv, TypeMetadata};
+    /// use jacob::x86::X86Backend;
+    ///
+    /// let pos = X86Backend {}.callconv_argpos_for(CallingConv::Win64, 4, TypeMetadata::Int64);
+    /// // the first 4 slots are the shadow space
+    /// assert_eq!(pos, Allocation::Stack { slot: 4, ty: TypeMetadata::Int64 });
+    /// ```
+    fn callconv_argpos_for(&self, conv: CallingConv, num: usize, ty: TypeMetadata) -> Allocation {
+        let regs = self.callconv_arg_regs(conv).unwrap_or_else(|| {
+            panic!(
+                "The calling convention {conv:?} is not supported by {}",
+                self.name()
+            )
+        });
+
+        match regs.get(num) {
+            Some(reg) => Allocation::Register { id: reg.id(), ty },
+            None => Allocation::Stack {
+                slot: self.callconv_shadow_space(conv) / self.data_layout().slot_size() + num
+                    - regs.len(),
+                ty,
+            },
+        }
+    }
+
+    /// Returns the number of stack slots the caller needs to reserve for a call with `args` arguments
+    fn callconv_call_slots(&self, conv: CallingConv, args: &[TypeMetadata]) -> usize {
+        let shadow = self.callconv_shadow_space(conv) / self.data_layout().slot_size();
+
+        args.iter()
+            .enumerate()
+            .filter_map(|(num, ty)| match self.callconv_argpos_for(conv, num, *ty) {
+                Allocation::Stack { slot, .. } => Some(slot + 1),
+                _ => None,
+            })
+            .fold(shadow, usize::max)
+    }
+
+    /// Returns if the register needs to be preserved by the callee in the calling convention
+    fn callconv_is_callee_saved(&self, conv: CallingConv, id: usize) -> bool {
+        self.callconv_callee_saved(conv)
+            .unwrap_or_default()
+            .iter()
+            .any(|reg| reg.id() == id)
+    }
+}
+
+/// This trait is used to lower calls
+pub trait CallLowering: BackendInst + CallConvInfos {
+    /// Returns the mnemonic of the call instruction
+    fn call_opcode(&self) -> &'static str;
+
+    /// Returns the number of bytes the call instruction pushes onto the stack (the return address)
+    fn call_pushed_bytes(&self) -> usize {
+        0
+    }
+
+    /// Returns if the call instruction stores the return address in a link register
+    /// (which functions that call others then have to preserve)
+    fn call_uses_link_reg(&self) -> bool {
+        false
+    }
+
+    /// Returns the size of a stack frame with the given amount of slots
+    ///
+    /// The size is chosen so that the stack pointer stays aligned for calls
+    fn frame_size(&self, slots: usize) -> usize {
+        let layout = self.data_layout();
+        let pushed = self.call_pushed_bytes();
+
+        (layout.slot_offset(slots) + pushed).next_multiple_of(layout.stack_align()) - pushed
+    }
+
+    /// Lowers a call of a function which uses the given calling convention
+    ///
+    /// The arguments are moved into the positions of the calling convention and the
+    /// returned value is moved from the return register into the output
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::codegen::{AllocatedIrNode, CallLowering, Reg, TargetArch};
+    /// use jacob::ir::{CallingConv, IrOpcode};
+    /// use jacob::x86::{RDI, RSI, X86Backend};
+    ///
+    /// // f(y, x) where x is in rdi and y is in rsi
+    /// let call = AllocatedIrNode::new(IrOpcode::Call, vec![RSI.alloc(), RDI.alloc()], false, None, None)
+    ///     .with_callee("f");
+    ///
+    /// let backend = TargetArch::X86.backend();
+    /// let insts = X86Backend {}.lower_call(&call, CallingConv::C);
+    ///
+    /// let asm: Vec<String> = insts.iter().map(|inst| backend.print_inst(inst)).collect();
+    /// assert_eq!(asm, ["\tmov rax, rsi\n", "\tmov rsi, rdi\n", "\tmov rdi, rax\n", "\tcall f\n"]);
+    /// ```
+    fn lower_call(&self, node: &AllocatedIrNode, conv: CallingConv) -> Vec<AssemblyInst> {
+        let mut insts = Vec::new();
+
+        let copy = |from: Allocation, to: Allocation| {
+            self.lower_inst(&AllocatedIrNode {
+                opcode: IrOpcode::Copy,
+                ops: vec![from],
+                has_out: true,
+                ty: Some(to.get_ty()),
+                alloc: Some(to),
+                loc: node.loc.clone(),
+                callee: None,
+            })
+        };
+
+        let moves: Vec<(Allocation, Allocation)> = node
+            .ops
+            .iter()
+            .enumerate()
+            .map(|(num, op)| (*op, self.callconv_argpos_for(conv, num, op.get_ty())))
+            .collect();
+
+        // the stack arguments are never read by other moves, so they are stored first
+        let (stack, regs): (Vec<_>, Vec<_>) = moves.iter().partition(|(_, to)| to.is_mem());
+
+        for (from, to) in stack.into_iter().filter(|(from, to)| from != to) {
+            if self.move_needs_scratch(from, to) {
+                let scratch = self.scratch_reg(&moves, conv, from.get_ty());
+                insts.extend(copy(from, scratch));
+                insts.extend(copy(scratch, to));
+            } else {
+                insts.extend(copy(from, to));
+            }
+        }
+
+        for (from, to) in self.sequence_moves(regs, conv) {
+            insts.extend(copy(from, to));
+        }
+
+        let callee = node
+            .callee
+            .as_deref()
+            .expect("A call node must have a callee");
+        insts.push(AssemblyInst::with_symbol(self.call_opcode(), callee));
+
+        if let Some(out) = node.alloc
+            && out != self.ret_reg()
+        {
+            insts.extend(copy(self.ret_reg(), out));
+        }
+
+        insts
+    }
+
+    /// Orders moves which happen in parallel so that no source is overwritten before it is read
+    ///
+    /// Cycles (like swapping two registers) are broken with a caller saved register of the
+    /// calling convention which isn't used by any of the moves (moves of a resource into
+    /// itself aren't emitted, but their register isn't used as the scratch register either)
+    fn sequence_moves(
+        &self,
+        moves: Vec<(Allocation, Allocation)>,
+        conv: CallingConv,
+    ) -> Vec<(Allocation, Allocation)> {
+        let mut pending: Vec<_> = moves
+            .iter()
+            .filter(|(from, to)| from != to)
+            .copied()
+            .collect();
+        let mut sequence = Vec::new();
+
+        while !pending.is_empty() {
+            // a move can be done once no other move still needs to read its target
+            if let Some(pos) = pending
+                .iter()
+                .position(|(_, to)| !pending.iter().any(|(from, _)| from == to))
+            {
+                sequence.push(pending.remove(pos));
+                continue;
+            }
+
+            // only cycles are left, so one of the sources is parked in a scratch register
+            let (from, _) = pending[0];
+            let scratch = self.scratch_reg(&moves, conv, from.get_ty());
+
+            sequence.push((from, scratch));
+
+            for (src, _) in &mut pending {
+                if *src == from {
+                    *src = scratch;
+                }
+            }
+        }
+
+        sequence
+    }
+
+    /// Returns if a move can't be done with a single copy and goes through a scratch register
+    ///
+    /// Memory can't be copied into memory directly and most targets can't store immediates
+    /// (immediates which the target can't encode were loaded into registers by the allocator)
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::codegen::TargetArch;
+    /// use jacob::ir::{Function, IrOperand, Module, TypeMetadata};
+    ///
+    /// // the last arguments are passed on the stack: some are forwarded stack arguments
+    /// // (memory to memory) and the last one is an immediate
+    /// let mut func = Function::new("f");
+    /// let mut args: Vec<IrOperand> = (0..9).map(|_| func.add_arg(TypeMetadata::Int64)).collect();
+    /// args.push(IrOperand::ConstNum { num: 5, ty: TypeMetadata::Int64 });
+    /// func.call("h", &args, None);
+    ///
+    /// let mut module = Module::new();
+    /// module.add_func(func);
+    ///
+    /// for (target, mem, imm) in [
+    ///     (
+    ///         TargetArch::X86,
+    ///         "\tmov rax, [rsp + 48]\n\tmov [rsp + 0], rax\n",
+    ///         "\tmov qword ptr [rsp + 24], 0x5\n",
+    ///     ),
+    ///     (
+    ///         TargetArch::Aarch64,
+    ///         "\tldr x8, [sp, #32]\n\tstr x8, [sp, #0]\n",
+    ///         "\tmov x8, #0x5\n\tstr x8, [sp, #8]\n",
+    ///     ),
+    ///     (
+    ///         TargetArch::Riscv64,
+    ///         "\tld s2, 32(sp)\n\tsd s2, 0(sp)\n",
+    ///         "\tli s2, 0x5\n\tsd s2, 8(sp)\n",
+    ///     ),
+    /// ] {
+    ///     let asm = module.compile(target, false).asm();
+    ///     assert!(asm.contains(mem));
+    ///     assert!(asm.contains(imm));
+    /// }
+    /// ```
+    fn move_needs_scratch(&self, from: Allocation, to: Allocation) -> bool {
+        to.is_mem() && !from.is_gr()
+    }
+
+    /// Returns a caller saved register of the calling convention which isn't used by any of the moves
+    fn scratch_reg(
+        &self,
+        moves: &[(Allocation, Allocation)],
+        conv: CallingConv,
+        ty: TypeMetadata,
+    ) -> Allocation {
+        self.grps()
+            .iter()
+            .filter(|reg| !self.callconv_is_callee_saved(conv, reg.id()))
+            .map(|reg| Allocation::Register { id: reg.id(), ty })
+            .find(|reg| !moves.iter().any(|(from, to)| from == reg || to == reg))
+            .unwrap_or_else(|| panic!("No scratch register left for the moves of a call"))
+    }
+}
+
+/// The trait to implement for describing the architecture in debug info
+pub trait DebugInfos {
+    /// Returns the dwarf register number for the register with the given id
+    fn dwarf_reg(&self, id: usize) -> Option<u16>;
+
+    /// Returns the dwarf register number of the stack pointer
+    fn dwarf_stack_reg(&self) -> u16;
 }
 
 /// The trait to implement for defining custom register
@@ -95,6 +564,42 @@ pub trait BackendInst {
 
     /// Gets the ir for the given assembly instruction
     fn disasm_inst(&self, asm: &[AssemblyInst]) -> (usize, AllocatedIrNode);
+
+    /// Returns if the number can be used as an immediate operand
+    /// (other numbers are loaded from the constant pool into a register)
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::codegen::TargetArch;
+    /// use jacob::ir::{Function, IrOperand, Module, TypeMetadata};
+    /// use jacob::opt::OptLevel;
+    ///
+    /// for target in [TargetArch::X86, TargetArch::Aarch64, TargetArch::Riscv64] {
+    ///     let mut func = Function::new("dec");
+    ///     let x = func.add_arg(TypeMetadata::Int64);
+    ///     let one = IrOperand::ConstNum { num: 1, ty: TypeMetadata::Int64 };
+    ///     let two = IrOperand::ConstNum { num: 2, ty: TypeMetadata::Int64 };
+    ///     // folded into 0xffffffffffffffff
+    ///     let minus_one = func.sub(&one, &two);
+    ///     let out = func.add(&x, &minus_one);
+    ///     func.ret(&out);
+    ///
+    ///     let mut module = Module::new();
+    ///     module.add_func(func);
+    ///     module.set_opt_level(OptLevel::O1);
+    ///
+    ///     let asm = module.compile(target, false).asm();
+    ///     assert!(asm.contains(".quad 0xffffffffffffffff"));
+    /// }
+    /// ```
+    fn is_legal_imm(&self, num: usize, _ty: TypeMetadata) -> bool {
+        num <= i32::MAX as usize
+    }
+
+    /// Returns if the instruction has no effect and can be removed
+    fn is_noop(&self, _inst: &AssemblyInst) -> bool {
+        false
+    }
 }
 
 /// This trait is used to lower instrincs
@@ -128,6 +633,8 @@ pub trait InstrincLowering: BackendInst + ArchInfos {
             has_out: true,
             ty: node.ty,
             alloc: Some(out),
+            loc: node.loc.clone(),
+            callee: None,
         })
     }
 }
@@ -138,33 +645,117 @@ pub trait AsmPrinter {
     fn print_compilation(&self, compilation: &Compilation) -> String {
         let mut out = self.print_comment("Compilation output");
 
+        out += self.print_header();
         out += self.print_code_section();
 
+        let mut files: Vec<&str> = Vec::new();
         for func in &compilation.funcs {
-            for c in &func.consts {
-                out += &self.print_const(c);
+            for inst in &func.insts {
+                if let Some(loc) = &inst.loc
+                    && !files.contains(&&*loc.file)
+                {
+                    files.push(&loc.file);
+                }
             }
+        }
+
+        for (index, file) in files.iter().enumerate() {
+            out += &self.print_file(index + 1, file);
+        }
 
-            if func.scope == Visibilty::Public {
-                out += &self.print_global(&func.name);
+        for (index, func) in compilation.funcs.iter().enumerate() {
+            if func.scope.is_declaration() {
+                out += &self.print_visibility(&func.name, &func.scope, &func.symbol_visibility);
+                continue;
             }
 
+            let own_section = func.attrs.section().is_some() || func.scope == Visibilty::LinkOnce;
+
+            if func.scope == Visibilty::LinkOnce {
+                out += &self.print_comdat_section(&func.name, func.attrs.section());
+            } else if let Some(section) = func.attrs.section() {
+                out += &self.print_section(section);
+            }
+
+            if let Some(align) = func.attrs.align() {
+                out += &self.print_align(align);
+            }
+
+            out += &self.print_visibility(&func.name, &func.scope, &func.symbol_visibility);
+
             out += &self.print_func_name(&func.name);
-            if func.meta_insts.is_empty() {
-                for inst in &func.insts {
+
+            let naked = func.attrs.has_attr(&FunctionAttribute::Naked);
+            if !naked {
+                out += &self.print_prologue(func);
+            }
+
+            // the instructions at which the location of an argument changes are labeled
+            let labels: Vec<usize> = match compilation.debug_info {
+                true => func
+                    .arg_ranges
+                    .iter()
+                    .flatten()
+                    .flat_map(|range| [range.start, range.end])
+                    .collect(),
+                false => Vec::new(),
+            };
+
+            let mut last_loc: Option<SourceLoc> = None;
+            let mut num = 0;
+            let mut print_insts = |insts: &Vec<AssemblyInst>| {
+                let mut out = String::new();
+                for inst in insts {
+                    if let Some(loc) = &inst.loc
+                        && last_loc.as_ref() != Some(loc)
+                    {
+                        let file = files.iter().position(|x| *x == &*loc.file).unwrap_or(0);
+                        out += &self.print_loc(file + 1, loc);
+                        last_loc = Some(loc.to_owned());
+                    }
+
+                    if labels.contains(&num) {
+                        out += &self.print_func_name(&inst_label(index, num));
+                    }
+                    num += 1;
+
+                    if !naked && inst.opcode == "ret" {
+                        out += &self.print_epilogue(func);
+                    }
+
                     out += &self.print_inst(inst);
                 }
+                out
+            };
+
+            if func.meta_insts.is_empty() {
+                out += &print_insts(&func.insts);
             } else {
                 for minst in &func.meta_insts {
                     if !minst.comment.is_empty() {
                         out += &self.print_comment(&minst.comment);
                     }
 
-                    for inst in &minst.insts {
-                        out += &self.print_inst(inst);
-                    }
+                    out += &print_insts(&minst.insts);
                 }
             }
+
+            if compilation.debug_info {
+                out += &self.print_func_name(&func_end_label(index));
+            }
+
+            if own_section {
+                out += self.print_code_section();
+            }
+        }
+
+        if !compilation.consts.is_empty() {
+            let endianness = compilation.arch.data_layout().endianness();
+
+            out += self.print_rodata_section();
+            for c in compilation.consts.iter() {
+                out += &self.print_const(c, endianness);
+            }
         }
 
         out
@@ -198,20 +789,121 @@ pub trait AsmPrinter {
             ops += &self.print_op(op);
         }
 
+        if let Some(symbol) = &inst.symbol {
+            if !ops.is_empty() {
+                ops += ", ";
+            }
+
+            ops += symbol;
+        }
+
         format!("\t{} {}\n", inst.opcode, ops)
     }
 
+    /// Prints the directive which assigns the number to a source file
+    fn print_file(&self, num: usize, file: &str) -> String {
+        format!(
+            ".file {num} \"{}\"\n",
+            file.replace('\\', "\\\\").replace('"', "\\\"")
+        )
+    }
+
+    /// Prints the directive which marks the following instructions as generated for `loc`
+    fn print_loc(&self, file: usize, loc: &SourceLoc) -> String {
+        format!(".loc {file} {} {}\n", loc.line, loc.column)
+    }
+
+    /// Prints the directives which configure the assembler for the whole file
+    fn print_header(&self) -> &'static str {
+        ""
+    }
+
     /// Prints the start for a code section
     fn print_code_section(&self) -> &'static str {
-        "section .text\n\n"
+        ".text\n"
     }
 
-    /// Prints a constant
-    fn print_const(&self, c: &Constant) -> String;
+    /// Prints the directive to switch into the given section
+    fn print_section(&self, name: &str) -> String {
+        format!(".section {name}\n")
+    }
 
-    /// Prints out the global visibility specifier
-    fn print_global(&self, func: &String) -> String {
-        format!("global {}", func)
+    /// Prints the directive to switch into a COMDAT section of which the linker keeps only one copy
+    ///
+    /// The section defaults to `.text.<name>` if no section is given
+    fn print_comdat_section(&self, name: &str, section: Option<&str>) -> String {
+        let section = match section {
+            Some(section) => section.to_owned(),
+            None => format!(".text.{name}"),
+        };
+
+        format!(".section {section},\"axG\",@progbits,{name},comdat\n")
+    }
+
+    /// Prints the directive to align the next symbol to `align` bytes
+    fn print_align(&self, align: usize) -> String {
+        format!(".p2align {}\n", align.trailing_zeros())
+    }
+
+    /// Prints the instructions which setup the stack frame of the function
+    fn print_prologue(&self, _func: &FuncAsm) -> String {
+        String::new()
+    }
+
+    /// Prints the instructions which tear down the stack frame before returning
+    fn print_epilogue(&self, _func: &FuncAsm) -> String {
+        String::new()
+    }
+
+    /// Prints the directive to switch into the section for read only data
+    fn print_rodata_section(&self) -> &'static str {
+        ".section .rodata\n"
+    }
+
+    /// Prints a constant (as quad words if it's aligned to 8 bytes, otherwise as bytes)
+    fn print_const(&self, c: &Constant, endianness: Endianness) -> String {
+        let mut out = self.print_align(c.align);
+        out += &self.print_func_name(&const_label(c.id));
+
+        let quads = if c.align >= 8 { c.bytes.len() / 8 } else { 0 };
+        let (words, rest) = c.bytes.split_at(quads * 8);
+
+        for word in words.chunks_exact(8) {
+            let word: [u8; 8] = word.try_into().expect("chunks have 8 bytes");
+            let num = match endianness {
+                Endianness::Little => u64::from_le_bytes(word),
+                Endianness::Big => u64::from_be_bytes(word),
+            };
+            out += &format!("\t.quad {num:#x}\n");
+        }
+
+        if !rest.is_empty() {
+            let bytes: Vec<String> = rest.iter().map(|x| format!("{x:#x}")).collect();
+            out += &format!("\t.byte {}\n", bytes.join(", "));
+        }
+
+        out
+    }
+
+    /// Prints out the linkage and visibility directives for the symbol
+    fn print_visibility(
+        &self,
+        name: &str,
+        scope: &Visibilty,
+        visibility: &SymbolVisibility,
+    ) -> String {
+        let linkage = match scope {
+            Visibilty::Internal => return format!(".local {name}\n"),
+            Visibilty::Public => format!(".globl {name}\n"),
+            Visibilty::Weak | Visibilty::LinkOnce => format!(".weak {name}\n"),
+            Visibilty::External => format!(".extern {name}\n"),
+        };
+
+        match visibility {
+            SymbolVisibility::Default => linkage,
+            SymbolVisibility::Protected => linkage + &format!(".protected {name}\n"),
+            SymbolVisibility::Hidden => linkage + &format!(".hidden {name}\n"),
+        }
     }
 }
 
@@ -1,9 +1,7 @@
-use std::{cell::RefCell, rc::Rc};
-
 use crate::{
-    codegen::{Compilation, FuncAsm, TargetArch},
+    codegen::{Compilation, FuncAsm, Target},
     decompile::{deregalloc::DeRegAlloc, type_extractor::TypeExtractor},
-    ir::{Function, IrOperand, Module},
+    ir::{Function, IrOperand, Module, NodeRef},
 };
 
 /// Helper structure to make decompilation much easier
@@ -36,7 +34,7 @@ impl DecompilationHelper {
     }
 
     /// Parses the function assembly of the given `FuncAsm` and returns an ir `Function`
-    pub fn add_func(&self, asm: &FuncAsm, target: &TargetArch) -> Function {
+    pub fn add_func(&self, asm: &FuncAsm, target: &Target) -> Function {
         let mut func = Function::new(&asm.name);
 
         let back = self.asm.arch.backend();
@@ -53,11 +51,9 @@ impl DecompilationHelper {
             }
         }
 
-        let mut deregalloc = DeRegAlloc::new(&alloc_ir, *target);
+        let mut deregalloc = DeRegAlloc::new(&alloc_ir, target.clone());
         deregalloc.dealloc();
 
-        eprintln!("{:#?}", deregalloc.ir()); // ToDo: remove log
-
         let mut ty_extractor = TypeExtractor::new(deregalloc.ir());
         ty_extractor.extract();
 
@@ -69,8 +65,9 @@ impl DecompilationHelper {
         func.ir = deregalloc
             .ir_owned()
             .iter()
-            .map(|node| IrOperand::Out(Rc::new(RefCell::new(node.to_owned()))))
+            .map(|node| IrOperand::Out(NodeRef::new(node.to_owned())))
             .collect();
+        func.own_nodes();
 
         func
     }
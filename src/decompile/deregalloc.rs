@@ -1,8 +1,8 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::collections::HashMap;
 
 use crate::{
-    codegen::{AllocatedIrNode, Allocation, TargetArch},
-    ir::{IrNode, IrOpcode, IrOperand},
+    codegen::{AllocatedIrNode, Allocation, Target},
+    ir::{IrNode, IrOpcode, IrOperand, NodeRef},
 };
 
 /// This helper structure is used to reverse enginner a list of
@@ -14,12 +14,12 @@ pub struct DeRegAlloc<'a> {
     allocated_ir: &'a Vec<AllocatedIrNode>,
     ir: Vec<IrNode>,
     inst_map: HashMap<Allocation, IrOperand>,
-    target: TargetArch,
+    target: Target,
 }
 
 impl<'a> DeRegAlloc<'a> {
     /// Creates a new deregalloc instance
-    pub fn new(allocated_ir: &'a Vec<AllocatedIrNode>, target: TargetArch) -> Self {
+    pub fn new(allocated_ir: &'a Vec<AllocatedIrNode>, target: Target) -> Self {
         Self {
             allocated_ir,
             ir: Vec::new(),
@@ -38,6 +38,9 @@ impl<'a> DeRegAlloc<'a> {
                 ops: Vec::new(),
                 has_out: inst.has_out,
                 ty: inst.ty,
+                callee: None,
+                call_attrs: Vec::new(),
+                loc: inst.loc.clone(),
             };
 
             if inst.opcode == IrOpcode::Ret // ToDo: won't work for good returns
@@ -61,7 +64,7 @@ impl<'a> DeRegAlloc<'a> {
             }
 
             if let Some(out) = &inst.alloc {
-                let op = IrOperand::Out(Rc::new(RefCell::new(node.to_owned())));
+                let op = IrOperand::Out(NodeRef::new(node.to_owned()));
 
                 *self.inst_map.entry(*out).or_insert(op.to_owned()) = op.to_owned();
             }
@@ -3,6 +3,7 @@ use crate::ir::{IrNode, TypeMetadata};
 /// Helper structure to reverse enginner the function type
 /// from the list of ir nodes
 pub struct TypeExtractor<'a> {
+    #[allow(dead_code)] // ToDo: read it once `extract` is implemented
     ir: &'a Vec<IrNode>,
     args: Vec<TypeMetadata>,
     ret: Option<TypeMetadata>,
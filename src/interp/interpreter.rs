@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+
+use crate::{
+    interp::{Trap, Value},
+    ir::{
+        Function, FunctionAttribute, InstrincType, IrNode, IrOpcode, IrOperand, Module, NodeRef,
+        TypeMetadata,
+    },
+};
+
+/// Executes ir functions with concrete arguments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interpreter {
+    step_limit: usize,
+    stack_ptr: u64,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Interpreter::new()
+    }
+}
+
+/// The state of a single function execution
+struct Frame<'a> {
+    args: &'a [Value],
+    values: HashMap<usize, Option<Value>>,
+}
+
+/// The state which is shared between all frames of one execution
+struct Context<'m> {
+    module: Option<&'m Module>,
+    steps: usize,
+    depth: usize,
+}
+
+impl Interpreter {
+    /// The default maximal number of executed nodes
+    pub const DEFAULT_STEP_LIMIT: usize = 1_000_000;
+
+    /// The maximal number of nested calls
+    pub const MAX_CALL_DEPTH: usize = 512;
+
+    /// Creates a new interpreter
+    pub fn new() -> Self {
+        Self {
+            step_limit: Self::DEFAULT_STEP_LIMIT,
+            stack_ptr: 0x7fff_0000,
+        }
+    }
+
+    /// Sets the maximal number of nodes to execute before trapping
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::interp::{Interpreter, Trap, Value};
+    /// use jacob::ir::{Function, TypeMetadata};
+    ///
+    /// let mut func = Function::new("double");
+    /// let x = func.add_arg(TypeMetadata::Int64);
+    /// func.set_ret(TypeMetadata::Int64);
+    ///
+    /// let result = func.add(&x, &x);
+    /// func.ret(&result);
+    ///
+    /// let interp = Interpreter::new().with_step_limit(1);
+    /// assert_eq!(interp.run(&func, &[Value::int64(1)]), Err(Trap::StepLimit(1)));
+    /// ```
+    pub fn with_step_limit(mut self, limit: usize) -> Self {
+        self.step_limit = limit;
+        self
+    }
+
+    /// Sets the value the get stack pointer instrinc returns
+    pub fn with_stack_ptr(mut self, stack_ptr: u64) -> Self {
+        self.stack_ptr = stack_ptr;
+        self
+    }
+
+    /// Runs the function with the given name from the module
+    ///
+    /// Calls inside of the function are resolved against the functions of the module
+    pub fn run_module(
+        &self,
+        module: &Module,
+        name: &str,
+        args: &[Value],
+    ) -> Result<Option<Value>, Trap> {
+        let mut ctx = Context {
+            module: Some(module),
+            steps: 0,
+            depth: 0,
+        };
+
+        self.call(name, args, &mut ctx)
+    }
+
+    /// Runs the function with the given arguments and returns the returned value
+    ///
+    /// The function must not contain calls, use `run_module` for that
+    pub fn run(&self, func: &Function, args: &[Value]) -> Result<Option<Value>, Trap> {
+        let mut ctx = Context {
+            module: None,
+            steps: 0,
+            depth: 0,
+        };
+
+        self.exec(func, args, &mut ctx)
+    }
+
+    /// Looks up the function in the module and runs it
+    fn call(&self, name: &str, args: &[Value], ctx: &mut Context) -> Result<Option<Value>, Trap> {
+        let Some(func) = ctx.module.and_then(|module| {
+            module
+                .funcs
+                .iter()
+                .find(|func| func.name == name && !func.visibility.is_declaration())
+        }) else {
+            return Err(Trap::UnknownFunction(name.to_owned()));
+        };
+
+        if ctx.depth >= Self::MAX_CALL_DEPTH {
+            return Err(Trap::CallDepth(Self::MAX_CALL_DEPTH));
+        }
+
+        ctx.depth += 1;
+        let out = self.exec(func, args, ctx);
+        ctx.depth -= 1;
+
+        out
+    }
+
+    /// Executes the function
+    fn exec(
+        &self,
+        func: &Function,
+        args: &[Value],
+        ctx: &mut Context,
+    ) -> Result<Option<Value>, Trap> {
+        if func.args.len() != args.len() {
+            return Err(Trap::ArgCount {
+                expected: func.args.len(),
+                got: args.len(),
+            });
+        }
+
+        for (ty, arg) in func.args.iter().zip(args) {
+            Interpreter::check_ty(*ty, arg)?;
+        }
+
+        let mut frame = Frame {
+            args,
+            values: HashMap::new(),
+        };
+
+        for op in &func.ir {
+            let IrOperand::Out(node) = op else {
+                continue;
+            };
+
+            if frame.values.contains_key(&node.id()) {
+                continue;
+            }
+
+            if node.borrow().is_ret() {
+                let ret = self.eval_ret(&node.borrow(), &mut frame, ctx)?;
+                Interpreter::check_returns(func)?;
+
+                return match (func.ret, ret) {
+                    (Some(ty), Some(value)) => {
+                        Interpreter::check_ty(ty, &value)?;
+                        Ok(Some(value))
+                    }
+                    (None, _) => Ok(None),
+                    (Some(_), None) => Err(Trap::NoValue),
+                };
+            }
+
+            self.eval_node(node, &mut frame, ctx)?;
+        }
+
+        match func.ret {
+            Some(_) => Err(Trap::MissingReturn),
+            None => Interpreter::check_returns(func).map(|_| None),
+        }
+    }
+
+    /// Returns an error if the function is marked as never returning
+    fn check_returns(func: &Function) -> Result<(), Trap> {
+        if func.has_attr(&FunctionAttribute::NoReturn) {
+            return Err(Trap::UnexpectedReturn(func.name.to_owned()));
+        }
+
+        Ok(())
+    }
+
+    /// Evaluates the operand of a return
+    fn eval_ret(
+        &self,
+        node: &IrNode,
+        frame: &mut Frame,
+        ctx: &mut Context,
+    ) -> Result<Option<Value>, Trap> {
+        self.step(ctx)?;
+
+        match node.ops.as_slice() {
+            [] => Ok(None),
+            [op] => self.eval_op(op, frame, ctx).map(Some),
+            _ => Err(Trap::InvalidOperands(node.opcode)),
+        }
+    }
+
+    /// Evaluates the node (if it wasn't already) and returns its output
+    fn eval_node(
+        &self,
+        node: &NodeRef,
+        frame: &mut Frame,
+        ctx: &mut Context,
+    ) -> Result<Option<Value>, Trap> {
+        if let Some(value) = frame.values.get(&node.id()) {
+            return Ok(*value);
+        }
+
+        self.step(ctx)?;
+
+        let node_ref = node.borrow();
+
+        let mut ops = Vec::new();
+        for op in &node_ref.ops {
+            ops.push(self.eval_op(op, frame, ctx)?);
+        }
+
+        let out = match (node_ref.opcode, ops.as_slice()) {
+            (IrOpcode::Add, [lhs, rhs]) => Some(Interpreter::binary(lhs, rhs)?.wrapping_add(rhs)),
+            (IrOpcode::Sub, [lhs, rhs]) => Some(Interpreter::binary(lhs, rhs)?.wrapping_sub(rhs)),
+            (IrOpcode::Copy, [op]) => Some(*op),
+            (IrOpcode::InstrincCall(settings), []) => match settings.instrinc {
+                InstrincType::GetStackPointer => Some(Value::new(
+                    node_ref.ty.unwrap_or(TypeMetadata::Int64),
+                    self.stack_ptr,
+                )),
+            },
+            (IrOpcode::Call, args) => {
+                let callee = node_ref.callee.as_deref().unwrap_or_default();
+                let out = self.call(callee, args, ctx)?;
+
+                if let (Some(ty), Some(value)) = (node_ref.ty, out) {
+                    Interpreter::check_ty(ty, &value)?;
+                }
+
+                if node_ref.has_out && out.is_none() {
+                    return Err(Trap::NoValue);
+                }
+
+                out
+            }
+            (IrOpcode::Ret, _) => return Err(Trap::NoValue),
+            (opcode, _) => return Err(Trap::InvalidOperands(opcode)),
+        };
+
+        let out = if node_ref.has_out { out } else { None };
+        frame.values.insert(node.id(), out);
+
+        Ok(out)
+    }
+
+    /// Evaluates an operand into a value
+    fn eval_op(&self, op: &IrOperand, frame: &mut Frame, ctx: &mut Context) -> Result<Value, Trap> {
+        match op {
+            IrOperand::Arg { num, ty } => {
+                let arg = frame.args.get(*num).ok_or(Trap::NoValue)?;
+                Interpreter::check_ty(*ty, arg)?;
+                Ok(*arg)
+            }
+            IrOperand::ConstNum { num, ty } => Ok(Value::new(*ty, *num as u64)),
+            IrOperand::Out(node) => self.eval_node(node, frame, ctx)?.ok_or(Trap::NoValue),
+            IrOperand::Drop(op) => self.eval_op(op, frame, ctx),
+        }
+    }
+
+    /// Checks that both operands of a binary operation have the same type
+    fn binary(lhs: &Value, rhs: &Value) -> Result<Value, Trap> {
+        Interpreter::check_ty(lhs.ty(), rhs)?;
+        Ok(*lhs)
+    }
+
+    /// Checks that the value has the expected type
+    fn check_ty(expected: TypeMetadata, value: &Value) -> Result<(), Trap> {
+        if value.ty() != expected {
+            return Err(Trap::TypeMismatch {
+                expected,
+                got: value.ty(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Counts an executed node
+    fn step(&self, ctx: &mut Context) -> Result<(), Trap> {
+        ctx.steps += 1;
+
+        if ctx.steps > self.step_limit {
+            return Err(Trap::StepLimit(self.step_limit));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+//! Reference interpreter for the ir
+//!
+//! The interpreter executes ir functions directly without going through any
+//! backend. It is meant for testing frontends and as a semantic oracle for
+//! optimizations and backends.
+//!
+//! Example:
+//! ```rust
+//! use jacob::interp::{Interpreter, Value};
+//! use jacob::ir::{Function, TypeMetadata};
+//!
+//! let mut func = Function::new("add");
+//! let x = func.add_arg(TypeMetadata::Int64);
+//! let y = func.add_arg(TypeMetadata::Int64);
+//! func.set_ret(TypeMetadata::Int64);
+//!
+//! let result = func.add(&x, &y);
+//! func.ret(&result);
+//!
+//! let out = Interpreter::new()
+//!     .run(&func, &[Value::int64(5), Value::int64(-7)])
+//!     .unwrap();
+//!
+//! assert_eq!(out, Some(Value::int64(-2)));
+//! ```
+
+/// The interpreter
+pub mod interpreter;
+/// Errors which stop the execution
+pub mod trap;
+/// Runtime values
+pub mod value;
+
+pub use interpreter::*;
+pub use trap::*;
+pub use value::*;
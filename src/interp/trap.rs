@@ -0,0 +1,58 @@
+use std::fmt::Display;
+
+use crate::ir::{IrOpcode, TypeMetadata};
+
+/// The reason why the execution of a function was stopped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Trap {
+    /// The function was called with the wrong number of arguments
+    ArgCount {
+        /// The number of arguments the function takes
+        expected: usize,
+        /// The number of arguments which were supplied
+        got: usize,
+    },
+    /// A value has a different type than expected
+    TypeMismatch {
+        /// The expected type
+        expected: TypeMetadata,
+        /// The type of the value
+        got: TypeMetadata,
+    },
+    /// An operand was used which doesn't produce a value
+    NoValue,
+    /// A node has the wrong number of operands
+    InvalidOperands(IrOpcode),
+    /// The function reached its end without returning
+    MissingReturn,
+    /// The maximal number of steps was executed
+    StepLimit(usize),
+    /// The function to run does not exist in the module
+    UnknownFunction(String),
+    /// The maximal number of nested calls was reached
+    CallDepth(usize),
+    /// The function is marked as `NoReturn` but returned
+    UnexpectedReturn(String),
+}
+
+impl Display for Trap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Trap::ArgCount { expected, got } => {
+                write!(f, "expected {expected} arguments but got {got}")
+            }
+            Trap::TypeMismatch { expected, got } => {
+                write!(f, "expected a value of type {expected:?} but got {got:?}")
+            }
+            Trap::NoValue => write!(f, "used an operand which does not produce a value"),
+            Trap::InvalidOperands(opcode) => write!(f, "invalid operands for {opcode:?}"),
+            Trap::MissingReturn => write!(f, "reached the end of the function without a return"),
+            Trap::StepLimit(limit) => write!(f, "exceeded the step limit of {limit}"),
+            Trap::UnknownFunction(name) => write!(f, "unknown function: {name}"),
+            Trap::CallDepth(depth) => write!(f, "exceeded the maximal call depth of {depth}"),
+            Trap::UnexpectedReturn(name) => write!(f, "the noreturn function {name} returned"),
+        }
+    }
+}
+
+impl std::error::Error for Trap {}
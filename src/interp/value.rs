@@ -0,0 +1,72 @@
+use std::fmt::Display;
+
+use crate::ir::TypeMetadata;
+
+/// A value the interpreter works with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Value {
+    ty: TypeMetadata,
+    bits: u64,
+}
+
+impl Value {
+    /// Creates a new value of the given type, the bits are truncated to the size of the type
+    pub fn new(ty: TypeMetadata, bits: u64) -> Self {
+        let width = ty.bitwidth();
+
+        let bits = if width >= 64 {
+            bits
+        } else {
+            bits & ((1u64 << width) - 1)
+        };
+
+        Self { ty, bits }
+    }
+
+    /// Creates a new signed 64 bit integer
+    pub fn int64(num: i64) -> Self {
+        Self::new(TypeMetadata::Int64, num as u64)
+    }
+
+    /// Returns the type of the value
+    pub fn ty(&self) -> TypeMetadata {
+        self.ty
+    }
+
+    /// Returns the raw bits of the value
+    pub fn bits(&self) -> u64 {
+        self.bits
+    }
+
+    /// Returns the value sign extended to an `i64` (for unsigned types it's zero extended)
+    pub fn as_i64(&self) -> i64 {
+        let width = self.ty.bitwidth();
+
+        if width >= 64 || !self.ty.signed() {
+            return self.bits as i64;
+        }
+
+        let shift = 64 - width;
+        ((self.bits << shift) as i64) >> shift
+    }
+
+    /// Wrapping addition
+    pub fn wrapping_add(&self, other: &Value) -> Value {
+        Value::new(self.ty, self.bits.wrapping_add(other.bits))
+    }
+
+    /// Wrapping subtraction
+    pub fn wrapping_sub(&self, other: &Value) -> Value {
+        Value::new(self.ty, self.bits.wrapping_sub(other.bits))
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.ty.signed() {
+            write!(f, "{} {:?}", self.as_i64(), self.ty)
+        } else {
+            write!(f, "{} {:?}", self.bits, self.ty)
+        }
+    }
+}
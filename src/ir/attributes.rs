@@ -0,0 +1,103 @@
+/// An attribute which can be attached to a function
+///
+/// Example:
+/// ```rust
+/// use jacob::codegen::TargetArch;
+/// use jacob::interp::{Interpreter, Trap, Value};
+/// use jacob::ir::{Function, FunctionAttribute, Module, TypeMetadata};
+///
+/// let mut fail = Function::new("fail");
+/// let code = fail.add_arg(TypeMetadata::Int64);
+/// fail.ret(&code);
+/// fail.add_attr(FunctionAttribute::NoReturn);
+///
+/// let mut func = Function::new("check");
+/// let x = func.add_arg(TypeMetadata::Int64);
+/// func.try_call(&fail, &[x.clone()]).unwrap();
+/// func.ret(&x);
+///
+/// let mut module = Module::new();
+/// module.add_func(fail);
+/// module.add_func(func);
+///
+/// // nothing after the call is emitted
+/// let asm = module.compile(TargetArch::X86, false).asm();
+/// assert!(asm.ends_with("\tcall fail\n"));
+///
+/// // fail returns even though it is marked as never returning
+/// assert_eq!(
+///     Interpreter::new().run_module(&module, "check", &[Value::int64(1)]),
+///     Err(Trap::UnexpectedReturn("fail".into()))
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FunctionAttribute {
+    /// The function should never be inlined (a hint for inliners, it conflicts with `AlwaysInline`)
+    NoInline,
+    /// The function should always be inlined (a hint for inliners, it conflicts with `NoInline`)
+    AlwaysInline,
+    /// The function is rarely called (it's placed in `.text.unlikely` if no section is given)
+    Cold,
+    /// The function never returns to the caller
+    ///
+    /// Code after a call of it isn't emitted and the interpreter traps if it returns
+    NoReturn,
+    /// The function has no prologue/epilogue, the body is emitted as is
+    Naked,
+    /// The function does not read or write memory
+    ///
+    /// Calls of it have no side effects, so they are removed if their output isn't used
+    ReadNone,
+    /// The minimum alignment of the function in bytes (must be a power of two)
+    Align(usize),
+    /// The section the function is placed in
+    Section(String),
+}
+
+impl FunctionAttribute {
+    /// Returns if the attribute describes the effects of a call, so it's also attached to
+    /// the calls of the function (see `IrNode::call_attrs`)
+    pub fn applies_to_calls(&self) -> bool {
+        matches!(
+            self,
+            FunctionAttribute::NoReturn | FunctionAttribute::ReadNone
+        )
+    }
+}
+
+/// Helper functions to query a list of attributes
+pub trait AttributeList {
+    /// Returns if the given attribute is in the list
+    fn has_attr(&self, attr: &FunctionAttribute) -> bool;
+
+    /// Returns the alignment if one is specified
+    fn align(&self) -> Option<usize>;
+
+    /// Returns the section the function is placed in if it isn't the default one
+    fn section(&self) -> Option<&str>;
+}
+
+impl AttributeList for [FunctionAttribute] {
+    fn has_attr(&self, attr: &FunctionAttribute) -> bool {
+        self.contains(attr)
+    }
+
+    fn align(&self) -> Option<usize> {
+        self.iter().rev().find_map(|attr| match attr {
+            FunctionAttribute::Align(align) => Some(*align),
+            _ => None,
+        })
+    }
+
+    fn section(&self) -> Option<&str> {
+        let section = self.iter().rev().find_map(|attr| match attr {
+            FunctionAttribute::Section(section) => Some(section.as_str()),
+            _ => None,
+        });
+
+        match section {
+            None if self.has_attr(&FunctionAttribute::Cold) => Some(".text.unlikely"),
+            section => section,
+        }
+    }
+}
@@ -0,0 +1,284 @@
+use std::{collections::HashMap, fmt::Display};
+
+use crate::ir::{Function, IrNode, IrOperand, NodeRef, TypeMetadata, Variable, walk_nodes};
+
+/// An error which occured while building ir with the checked builder functions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    /// The operand has a different type than expected
+    TypeMismatch {
+        /// The expected type
+        expected: TypeMetadata,
+        /// The type of the operand
+        got: TypeMetadata,
+    },
+    /// The function is called with the wrong number of arguments
+    ArgCount {
+        /// The called function
+        callee: String,
+        /// The number of arguments the function has
+        expected: usize,
+        /// The number of arguments it was called with
+        got: usize,
+    },
+    /// The argument does not exist in the function
+    UnknownArg(usize),
+    /// The operand is the output of a node which doesn't produce a value
+    NoValue,
+    /// The operand is the output of a node of another function
+    ForeignValue,
+    /// The variable is not declared in the function
+    UnknownVar(Variable),
+    /// The variable is used before it is defined
+    UndefinedVar(Variable),
+    /// The returned value does not match the return type of the function
+    RetMismatch {
+        /// The return type of the function
+        expected: Option<TypeMetadata>,
+        /// The type of the returned value
+        got: Option<TypeMetadata>,
+    },
+}
+
+/// The nodes which belong to a function, keyed by their id
+///
+/// The nodes are kept alive, so that their ids can't be reused by nodes of other functions
+#[derive(Clone, Default)]
+pub(crate) struct OwnedNodes(HashMap<usize, NodeRef>);
+
+impl OwnedNodes {
+    /// Adds the node to the function
+    pub(crate) fn insert(&mut self, node: &NodeRef) {
+        self.0.entry(node.id()).or_insert_with(|| node.to_owned());
+    }
+
+    /// Returns if the node belongs to the function
+    pub(crate) fn contains(&self, node: &NodeRef) -> bool {
+        self.0.contains_key(&node.id())
+    }
+
+    /// Returns all nodes of the function
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &NodeRef> {
+        self.0.values()
+    }
+}
+
+impl std::fmt::Debug for OwnedNodes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("OwnedNodes").field(&self.0.len()).finish()
+    }
+}
+
+impl Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuildError::TypeMismatch { expected, got } => {
+                write!(
+                    f,
+                    "expected an operand of type {expected:?} but got {got:?}"
+                )
+            }
+            BuildError::ArgCount {
+                callee,
+                expected,
+                got,
+            } => write!(
+                f,
+                "`{callee}` takes {expected} arguments but was called with {got}"
+            ),
+            BuildError::UnknownArg(num) => write!(f, "the function has no argument {num}"),
+            BuildError::NoValue => write!(f, "the operand does not produce a value"),
+            BuildError::ForeignValue => {
+                write!(f, "the operand is a value of another function")
+            }
+            BuildError::UnknownVar(var) => {
+                write!(f, "the variable {var:?} is not declared in the function")
+            }
+            BuildError::UndefinedVar(var) => {
+                write!(f, "the variable {var:?} is used before it is defined")
+            }
+            BuildError::RetMismatch { expected, got } => write!(
+                f,
+                "the function returns {expected:?} but the returned value is {got:?}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+macro_rules! checked_op2 {
+    ($name:tt, $unchecked:tt) => {
+        #[doc = concat!("Same as `", stringify!($unchecked), "` but checks that both operands have the same type")]
+        pub fn $name(&mut self, lhs: &IrOperand, rhs: &IrOperand) -> Result<IrOperand, BuildError> {
+            self.check_op(lhs)?;
+            self.check_op(rhs)?;
+            Function::check_ty(lhs.get_ty(), rhs)?;
+
+            Ok(self.$unchecked(lhs, rhs))
+        }
+    };
+}
+
+impl Function {
+    checked_op2!(try_add, add);
+    checked_op2!(try_sub, sub);
+
+    /// Same as `copy` but checks that the operand belongs to the function
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::ir::{BuildError, Function, TypeMetadata};
+    ///
+    /// let mut other = Function::new("other");
+    /// other.add_arg(TypeMetadata::Int64);
+    /// let y = other.add_arg(TypeMetadata::Int64);
+    ///
+    /// let sum = other.add(&y, &y);
+    ///
+    /// let mut func = Function::new("func");
+    /// assert_eq!(func.try_copy(&y), Err(BuildError::UnknownArg(1)));
+    /// assert_eq!(func.try_copy(&sum), Err(BuildError::ForeignValue));
+    /// ```
+    pub fn try_copy(&mut self, op: &IrOperand) -> Result<IrOperand, BuildError> {
+        self.check_op(op)?;
+        Ok(self.copy(op))
+    }
+
+    /// Same as `ret` but checks that the value matches the type given to `set_ret`
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::ir::{BuildError, Function, TypeMetadata};
+    ///
+    /// let mut func = Function::new("nothing");
+    /// let x = func.add_arg(TypeMetadata::Int64);
+    ///
+    /// assert_eq!(
+    ///     func.try_ret(&x),
+    ///     Err(BuildError::RetMismatch {
+    ///         expected: None,
+    ///         got: Some(TypeMetadata::Int64)
+    ///     })
+    /// );
+    /// ```
+    pub fn try_ret(&mut self, op: &IrOperand) -> Result<(), BuildError> {
+        self.check_op(op)?;
+
+        if self.ret != Some(op.get_ty()) {
+            return Err(BuildError::RetMismatch {
+                expected: self.ret,
+                got: Some(op.get_ty()),
+            });
+        }
+
+        self.ret(op);
+        Ok(())
+    }
+
+    /// Same as `call` but checks the arguments against the signature of the callee
+    ///
+    /// The attributes of the callee which describe the effects of the call
+    /// (like `ReadNone`) are attached to the call
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::ir::{BuildError, Function, TypeMetadata};
+    ///
+    /// let mut callee = Function::new("inc");
+    /// let x = callee.add_arg(TypeMetadata::Int64);
+    /// callee.set_ret(TypeMetadata::Int64);
+    /// callee.ret(&x);
+    ///
+    /// let mut func = Function::new("main");
+    /// assert_eq!(
+    ///     func.try_call(&callee, &[]),
+    ///     Err(BuildError::ArgCount {
+    ///         callee: "inc".into(),
+    ///         expected: 1,
+    ///         got: 0
+    ///     })
+    /// );
+    /// ```
+    pub fn try_call(
+        &mut self,
+        callee: &Function,
+        args: &[IrOperand],
+    ) -> Result<IrOperand, BuildError> {
+        if callee.args.len() != args.len() {
+            return Err(BuildError::ArgCount {
+                callee: callee.name.to_owned(),
+                expected: callee.args.len(),
+                got: args.len(),
+            });
+        }
+
+        for (ty, arg) in callee.args.iter().zip(args) {
+            self.check_op(arg)?;
+            Function::check_ty(*ty, arg)?;
+        }
+
+        let call = self.call(&callee.name, args, callee.ret);
+        call.force_node().borrow_mut().call_attrs = callee
+            .attrs
+            .iter()
+            .filter(|attr| attr.applies_to_calls())
+            .cloned()
+            .collect();
+
+        Ok(call)
+    }
+
+    /// Checks that the operand is a value which is usable inside of the function
+    pub(crate) fn check_op(&self, op: &IrOperand) -> Result<(), BuildError> {
+        match op {
+            IrOperand::Arg { num, ty } => match self.args.get(*num) {
+                Some(arg) if arg == ty => Ok(()),
+                Some(arg) => Err(BuildError::TypeMismatch {
+                    expected: *arg,
+                    got: *ty,
+                }),
+                None => Err(BuildError::UnknownArg(*num)),
+            },
+            IrOperand::Drop(op) => self.check_op(op),
+            IrOperand::Out(node) if !node.borrow().has_out => Err(BuildError::NoValue),
+            IrOperand::Out(node) if self.nodes.contains(node) => Ok(()),
+            IrOperand::Out(_) => Err(BuildError::ForeignValue),
+            IrOperand::ConstNum { .. } => Ok(()),
+        }
+    }
+
+    /// Adds the nodes of the ir which weren't created by the builder (e.g. by a pass)
+    /// to the nodes of the function
+    pub(crate) fn own_nodes(&mut self) {
+        for node in walk_nodes(&self.ir) {
+            self.nodes.insert(&node);
+        }
+    }
+
+    /// Checks that the operand has the expected type
+    pub(crate) fn check_ty(expected: TypeMetadata, op: &IrOperand) -> Result<(), BuildError> {
+        if op.get_ty() != expected {
+            return Err(BuildError::TypeMismatch {
+                expected,
+                got: op.get_ty(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl IrNode {
+    /// Same as `add` but checks that both operands have the same type
+    pub fn try_add(lhs: &IrOperand, rhs: &IrOperand) -> Result<IrOperand, BuildError> {
+        Function::check_ty(lhs.get_ty(), rhs)?;
+        Ok(IrNode::add(lhs, rhs))
+    }
+
+    /// Same as `sub` but checks that both operands have the same type
+    pub fn try_sub(lhs: &IrOperand, rhs: &IrOperand) -> Result<IrOperand, BuildError> {
+        Function::check_ty(lhs.get_ty(), rhs)?;
+        Ok(IrNode::sub(lhs, rhs))
+    }
+}
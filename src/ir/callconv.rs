@@ -0,0 +1,20 @@
+/// The calling convention of a function
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CallingConv {
+    /// The default C calling convention of the target
+    #[default]
+    C,
+    /// The System V AMD64 calling convention (x86)
+    SysV,
+    /// The Microsoft x64 calling convention (x86)
+    Win64,
+    /// The procedure call standard for the arm 64 bit architecture (aarch64)
+    Aapcs64,
+    /// The LP64 integer calling convention (riscv64)
+    RiscvLp64,
+    /// An internal calling convention which passes more arguments in registers,
+    /// only usable for functions which are not called from outside of the module
+    Fast,
+    /// Like the default convention but the callee preserves nearly all registers
+    PreserveMost,
+}
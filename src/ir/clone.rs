@@ -0,0 +1,197 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crate::ir::{Function, IrNode, IrOperand, NodeRef, OwnedNodes};
+
+/// The key of an old value in a `ValueMap`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ValueKey {
+    Arg(usize),
+    Node(usize),
+}
+
+/// Maps values of one function to the values of another one
+///
+/// It is used to deep clone ir: nodes which are not yet in the map are cloned
+/// on first use, so nodes which were shared in the original ir are also shared
+/// in the cloned ir. Entries can be added upfront, e.g: to replace the arguments
+/// of an inlined function with the operands of the call
+#[derive(Debug, Clone, Default)]
+pub struct ValueMap {
+    // the old node is kept alive, so that its id stays unique
+    map: HashMap<ValueKey, (Option<NodeRef>, IrOperand)>,
+}
+
+impl ValueMap {
+    /// Creates a new empty value map
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maps the old value (an argument or node output) to the new operand
+    pub fn insert(&mut self, old: &IrOperand, new: IrOperand) {
+        match old {
+            IrOperand::Arg { num, .. } => {
+                self.map.insert(ValueKey::Arg(*num), (None, new));
+            }
+            IrOperand::Out(node) => {
+                self.map
+                    .insert(ValueKey::Node(node.id()), (Some(node.to_owned()), new));
+            }
+            IrOperand::Drop(op) => self.insert(op, new),
+            IrOperand::ConstNum { .. } => {}
+        }
+    }
+
+    /// Returns the new operand for the old value (if it is mapped)
+    pub fn get(&self, old: &IrOperand) -> Option<&IrOperand> {
+        match old {
+            IrOperand::Arg { num, .. } => self.map.get(&ValueKey::Arg(*num)).map(|x| &x.1),
+            IrOperand::Out(node) => self.map.get(&ValueKey::Node(node.id())).map(|x| &x.1),
+            IrOperand::Drop(op) => self.get(op),
+            IrOperand::ConstNum { .. } => None,
+        }
+    }
+
+    /// Returns the new operand for the old one, nodes which aren't mapped yet are cloned
+    ///
+    /// The unmapped operands of a node are cloned before it with an explicit stack,
+    /// so long chains of nodes don't overflow the stack
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::ir::{Function, IrOperand, TypeMetadata, ValueMap};
+    ///
+    /// let mut func = Function::new("long");
+    /// let mut value = func.add_arg(TypeMetadata::Int64);
+    /// let one = IrOperand::ConstNum { num: 1, ty: TypeMetadata::Int64 };
+    ///
+    /// for _ in 0..100_000 {
+    ///     value = func.add(&value, &one);
+    /// }
+    ///
+    /// let mut map = ValueMap::new();
+    /// let new = map.remap(&value);
+    /// assert!(!new.force_node().ptr_eq(value.force_node()));
+    /// assert_eq!(map.get(&func.ir()[0]).unwrap().force_node().borrow().ops()[1], one);
+    /// ```
+    pub fn remap(&mut self, old: &IrOperand) -> IrOperand {
+        match old {
+            IrOperand::Arg { .. } | IrOperand::ConstNum { .. } => {
+                self.get(old).cloned().unwrap_or_else(|| old.to_owned())
+            }
+            IrOperand::Out(node) => {
+                // the flag is set once the operands of the node were pushed
+                let mut stack = vec![(node.to_owned(), false)];
+
+                while let Some((node, expanded)) = stack.pop() {
+                    if self.is_mapped(&node) {
+                        continue;
+                    }
+
+                    if expanded {
+                        let new = IrOperand::Out(NodeRef::new(self.remap_node(&node.borrow())));
+                        self.insert(&IrOperand::Out(node), new);
+                        continue;
+                    }
+
+                    stack.push((node.to_owned(), true));
+                    for op in node.borrow().ops.iter() {
+                        if let Some(used) = op.node()
+                            && !self.is_mapped(used)
+                        {
+                            stack.push((used.to_owned(), false));
+                        }
+                    }
+                }
+
+                self.get(old).cloned().expect("the node was just mapped")
+            }
+            IrOperand::Drop(op) => IrOperand::Drop(Arc::new(self.remap(op))),
+        }
+    }
+
+    /// Returns if the node is already mapped
+    fn is_mapped(&self, node: &NodeRef) -> bool {
+        self.map.contains_key(&ValueKey::Node(node.id()))
+    }
+
+    /// Remaps all operands of the ir
+    pub fn remap_all(&mut self, ir: &[IrOperand]) -> Vec<IrOperand> {
+        ir.iter().map(|op| self.remap(op)).collect()
+    }
+
+    /// Clones the node with remapped operands
+    pub fn remap_node(&mut self, node: &IrNode) -> IrNode {
+        let mut new = node.to_owned();
+        new.ops = node.ops.iter().map(|op| self.remap(op)).collect();
+        new
+    }
+}
+
+impl Function {
+    /// Deep clones the function and returns the map from the old to the new values
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::ir::{Function, TypeMetadata};
+    ///
+    /// let mut func = Function::new("add");
+    /// let x = func.add_arg(TypeMetadata::Int64);
+    /// let out = func.add(&x, &x);
+    /// func.ret(&out);
+    ///
+    /// let (copy, map) = func.clone_with_map();
+    /// assert_eq!(copy, func);
+    ///
+    /// let new = map.get(&out).unwrap();
+    /// assert!(!new.force_node().ptr_eq(out.force_node()));
+    /// assert!(new.force_node().ptr_eq(copy.ir()[0].force_node()));
+    /// ```
+    pub fn clone_with_map(&self) -> (Function, ValueMap) {
+        let mut map = ValueMap::new();
+        let func = self.clone_into_map(&mut map);
+        (func, map)
+    }
+
+    /// Deep clones the function using the given value map
+    pub fn clone_into_map(&self, map: &mut ValueMap) -> Function {
+        let ir = map.remap_all(&self.ir);
+        let var_defs = self
+            .var_defs
+            .iter()
+            .map(|(var, value)| (*var, map.remap(value)))
+            .collect();
+
+        // only the nodes which are still used have a copy
+        let mut nodes = OwnedNodes::default();
+        for node in self.nodes.iter() {
+            if let Some(IrOperand::Out(new)) = map.get(&IrOperand::Out(node.to_owned())) {
+                nodes.insert(new);
+            }
+        }
+
+        Function {
+            name: self.name.to_owned(),
+            ret: self.ret,
+            args: self.args.to_owned(),
+            ir,
+            nodes,
+            visibility: self.visibility,
+            symbol_visibility: self.symbol_visibility,
+            callconv: self.callconv,
+            attrs: self.attrs.to_owned(),
+            metadata: self.metadata.to_owned(),
+            cur_loc: self.cur_loc.to_owned(),
+            id: self.id,
+            vars: self.vars.to_owned(),
+            var_defs,
+        }
+    }
+}
+
+impl Clone for Function {
+    /// Deep clones the function, so the nodes of the clone are independent from the original
+    fn clone(&self) -> Self {
+        self.clone_into_map(&mut ValueMap::new())
+    }
+}
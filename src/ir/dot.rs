@@ -0,0 +1,118 @@
+use std::{collections::HashMap, fmt::Write};
+
+use crate::ir::{Function, IrOperand, NodeRef};
+
+impl Function {
+    /// Renders the dataflow graph of the function in the graphviz dot format
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::ir::{Function, TypeMetadata};
+    ///
+    /// let mut func = Function::new("add");
+    /// let x = func.add_arg(TypeMetadata::Int64);
+    /// let y = func.add_arg(TypeMetadata::Int64);
+    /// let out = func.add(&x, &y);
+    /// func.ret(&out);
+    ///
+    /// let dot = func.to_dot();
+    /// assert!(dot.starts_with("digraph \"add\" {"));
+    /// assert!(dot.contains("arg0 -> n0"));
+    /// assert!(dot.contains("n0 -> n1"));
+    /// ```
+    pub fn to_dot(&self) -> String {
+        ir_to_dot(&self.name, &self.ir)
+    }
+}
+
+/// Renders the dataflow graph of the given ir in the graphviz dot format
+///
+/// Edges for operands which drop their value are dashed
+pub fn ir_to_dot(name: &str, ir: &[IrOperand]) -> String {
+    let mut graph = DotGraph {
+        ids: HashMap::new(),
+        out: String::new(),
+        consts: 0,
+    };
+
+    for op in ir {
+        if let IrOperand::Out(node) = op {
+            graph.node(node);
+        }
+    }
+
+    format!(
+        "digraph \"{}\" {{\n\tnode [shape=box];\n{}}}\n",
+        escape(name),
+        graph.out
+    )
+}
+
+/// Escapes a string so it can be used inside of a quoted dot label
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+struct DotGraph {
+    ids: HashMap<usize, usize>,
+    out: String,
+    consts: usize,
+}
+
+impl DotGraph {
+    /// Prints the node (and all nodes it depends on) and returns its name
+    fn node(&mut self, node: &NodeRef) -> String {
+        if let Some(id) = self.ids.get(&node.id()) {
+            return format!("n{id}");
+        }
+
+        let id = self.ids.len();
+        self.ids.insert(node.id(), id);
+
+        let node = node.borrow();
+
+        let mut label = format!("{:?}", node.opcode).to_lowercase();
+        if let Some(callee) = &node.callee {
+            label += &format!(" {callee}");
+        }
+        if let Some(ty) = node.ty {
+            label += &format!(": {ty:?}");
+        }
+
+        let _ = writeln!(self.out, "\tn{id} [label=\"{}\"];", escape(&label));
+
+        for (index, op) in node.ops.iter().enumerate() {
+            let style = if op.is_drop() { ", style=dashed" } else { "" };
+            let from = self.operand(op);
+
+            let _ = writeln!(self.out, "\t{from} -> n{id} [label=\"{index}\"{style}];");
+        }
+
+        format!("n{id}")
+    }
+
+    /// Prints the operand and returns its name
+    fn operand(&mut self, op: &IrOperand) -> String {
+        match op {
+            IrOperand::Arg { num, ty } => {
+                let name = format!("arg{num}");
+                let _ = writeln!(
+                    self.out,
+                    "\t{name} [label=\"arg{num}: {ty:?}\", shape=ellipse];"
+                );
+                name
+            }
+            IrOperand::ConstNum { num, ty } => {
+                let name = format!("c{}", self.consts);
+                self.consts += 1;
+                let _ = writeln!(
+                    self.out,
+                    "\t{name} [label=\"{num}: {ty:?}\", shape=plaintext];"
+                );
+                name
+            }
+            IrOperand::Out(node) => self.node(node),
+            IrOperand::Drop(op) => self.operand(op),
+        }
+    }
+}
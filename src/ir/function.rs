@@ -1,7 +1,15 @@
-use crate::ir::{IrNode, operand::IrOperand, ty::TypeMetadata, visibility::Visibilty};
+use std::collections::BTreeMap;
+
+use crate::ir::{
+    AttributeList, CallingConv, FunctionAttribute, IrNode, OwnedNodes, SourceLoc, Variable,
+    next_func_id,
+    operand::IrOperand,
+    ty::TypeMetadata,
+    visibility::{SymbolVisibility, Visibilty},
+};
 
 /// Saves the ir code for a function
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug)]
 pub struct Function {
     pub(crate) name: String,
 
@@ -9,9 +17,56 @@ pub struct Function {
     pub(crate) args: Vec<TypeMetadata>,
 
     pub(crate) ir: Vec<IrOperand>,
+    pub(crate) nodes: OwnedNodes,
     pub(crate) visibility: Visibilty,
+    pub(crate) symbol_visibility: SymbolVisibility,
+    pub(crate) callconv: CallingConv,
+
+    pub(crate) attrs: Vec<FunctionAttribute>,
+    pub(crate) metadata: BTreeMap<String, String>,
+
+    pub(crate) cur_loc: Option<SourceLoc>,
+
+    pub(crate) id: usize,
+    pub(crate) vars: Vec<TypeMetadata>,
+    pub(crate) var_defs: BTreeMap<Variable, IrOperand>,
 }
 
+impl PartialEq for Function {
+    /// Compares the signatures, ir and attributes
+    ///
+    /// The builder state (variables and the source location) and the id aren't
+    /// compared, so a function equals the copy of it which was built separately
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::ir::{Function, TypeMetadata};
+    ///
+    /// let build = |with_var: bool| {
+    ///     let mut func = Function::new("id");
+    ///     func.set_ret(TypeMetadata::Int64);
+    ///     let x = func.add_arg(TypeMetadata::Int64);
+    ///     if with_var {
+    ///         let var = func.declare_var(TypeMetadata::Int64);
+    ///         func.def_var(var, &x);
+    ///     }
+    ///     func.ret(&x);
+    ///     func
+    /// };
+    ///
+    /// assert_eq!(build(false), build(true));
+    /// ```
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.args == other.args
+            && self.ret == other.ret
+            && self.ir == other.ir
+            && self.attrs == other.attrs
+    }
+}
+
+impl Eq for Function {}
+
 impl Function {
     /// Creates a new (public) function
     pub fn new(name: &str) -> Self {
@@ -21,10 +76,27 @@ impl Function {
             args: Vec::new(),
 
             ir: Vec::new(),
+            nodes: OwnedNodes::default(),
             visibility: Visibilty::Public,
+            symbol_visibility: SymbolVisibility::Default,
+            callconv: CallingConv::C,
+
+            attrs: Vec::new(),
+            metadata: BTreeMap::new(),
+
+            cur_loc: None,
+
+            id: next_func_id(),
+            vars: Vec::new(),
+            var_defs: BTreeMap::new(),
         }
     }
 
+    /// Returns the name of the function
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     /// Sets the visibility of the function to `Internal`
     pub fn internal(&mut self) {
         self.visibility = Visibilty::Internal;
@@ -35,6 +107,223 @@ impl Function {
         self.visibility = Visibilty::Public;
     }
 
+    /// Sets the visibility/linkage of the function
+    ///
+    /// A function with the `External` visibility is only a declaration, its ir is ignored
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::codegen::TargetArch;
+    /// use jacob::ir::{Function, Module, TypeMetadata, visibility::Visibilty};
+    ///
+    /// let mut func = Function::new("fallback");
+    /// let x = func.add_arg(TypeMetadata::Int64);
+    /// func.ret(&x);
+    /// func.set_visibility(Visibilty::Weak);
+    ///
+    /// let mut module = Module::new();
+    /// module.add_func(func);
+    ///
+    /// let asm = module.compile(TargetArch::Riscv64, false).asm();
+    /// assert!(asm.contains(".weak fallback\nfallback:\n"));
+    /// ```
+    pub fn set_visibility(&mut self, visibility: Visibilty) {
+        self.visibility = visibility;
+    }
+
+    /// Returns the visibility/linkage of the function
+    pub fn visibility(&self) -> Visibilty {
+        self.visibility
+    }
+
+    /// Sets the visibility of the symbol outside of the shared library it is linked into
+    ///
+    /// It is ignored for `Internal` functions
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::codegen::TargetArch;
+    /// use jacob::ir::{Function, Module, TypeMetadata};
+    /// use jacob::ir::visibility::{SymbolVisibility, Visibilty};
+    ///
+    /// let mut func = Function::new("helper");
+    /// let x = func.add_arg(TypeMetadata::Int64);
+    /// func.ret(&x);
+    /// func.set_visibility(Visibilty::Weak);
+    /// func.set_symbol_visibility(SymbolVisibility::Hidden);
+    ///
+    /// let mut module = Module::new();
+    /// module.add_func(func);
+    ///
+    /// let asm = module.compile(TargetArch::Riscv64, false).asm();
+    /// assert!(asm.contains(".weak helper\n.hidden helper\nhelper:\n"));
+    /// ```
+    pub fn set_symbol_visibility(&mut self, visibility: SymbolVisibility) {
+        self.symbol_visibility = visibility;
+    }
+
+    /// Returns the visibility of the symbol outside of the shared library it is linked into
+    pub fn symbol_visibility(&self) -> SymbolVisibility {
+        self.symbol_visibility
+    }
+
+    /// Sets the calling convention of the function
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::codegen::TargetArch;
+    /// use jacob::ir::{CallingConv, Function, Module, TypeMetadata};
+    ///
+    /// let mut func = Function::new("first");
+    /// let x = func.add_arg(TypeMetadata::Int64);
+    /// func.set_ret(TypeMetadata::Int64);
+    /// func.ret(&x);
+    /// func.set_callconv(CallingConv::Win64);
+    ///
+    /// let mut caller = Function::new("main");
+    /// let y = caller.add_arg(TypeMetadata::Int64);
+    /// caller.set_ret(TypeMetadata::Int64);
+    /// let out = caller.call("first", &[y], Some(TypeMetadata::Int64));
+    /// caller.ret(&out);
+    ///
+    /// let mut module = Module::new();
+    /// module.add_func(func);
+    /// module.add_func(caller);
+    ///
+    /// let asm = module.compile(TargetArch::X86, false).asm();
+    /// assert!(asm.contains("first:\n\tmov rax, rcx\n"));
+    /// // the caller reserves the 32 bytes of shadow space
+    /// assert!(asm.contains("main:\n\tsub rsp, 40\n\tmov rcx, rdi\n\tcall first\n"));
+    /// ```
+    pub fn set_callconv(&mut self, callconv: CallingConv) {
+        self.callconv = callconv;
+    }
+
+    /// Returns the calling convention of the function
+    pub fn callconv(&self) -> CallingConv {
+        self.callconv
+    }
+
+    /// Adds an attribute to the function
+    ///
+    /// Panics if the alignment of an `Align` attribute isn't a power of two (or zero)
+    /// or if `NoInline` and `AlwaysInline` are both added
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::codegen::TargetArch;
+    /// use jacob::ir::{Function, FunctionAttribute, Module, TypeMetadata};
+    ///
+    /// let mut func = Function::new("hot");
+    /// let x = func.add_arg(TypeMetadata::Int64);
+    /// func.ret(&x);
+    /// func.add_attr(FunctionAttribute::Align(16));
+    /// func.add_attr(FunctionAttribute::Section(".text.hot".into()));
+    ///
+    /// let mut module = Module::new();
+    /// module.add_func(func);
+    ///
+    /// let asm = module.compile(TargetArch::Aarch64, false).asm();
+    /// assert!(asm.contains(".section .text.hot\n.p2align 4\n"));
+    /// ```
+    ///
+    /// Rarely called functions are placed in their own section:
+    /// ```rust
+    /// use jacob::codegen::TargetArch;
+    /// use jacob::ir::{Function, FunctionAttribute, Module, TypeMetadata};
+    ///
+    /// let mut func = Function::new("on_error");
+    /// let x = func.add_arg(TypeMetadata::Int64);
+    /// func.ret(&x);
+    /// func.add_attr(FunctionAttribute::Cold);
+    ///
+    /// let mut module = Module::new();
+    /// module.add_func(func);
+    ///
+    /// let asm = module.compile(TargetArch::X86, false).asm();
+    /// assert!(asm.contains(".section .text.unlikely\n.globl on_error\n"));
+    /// ```
+    ///
+    /// ```rust,should_panic
+    /// use jacob::ir::{Function, FunctionAttribute};
+    ///
+    /// Function::new("odd").add_attr(FunctionAttribute::Align(12));
+    /// ```
+    ///
+    /// ```rust,should_panic
+    /// use jacob::ir::{Function, FunctionAttribute};
+    ///
+    /// let mut func = Function::new("undecided");
+    /// func.add_attr(FunctionAttribute::NoInline);
+    /// func.add_attr(FunctionAttribute::AlwaysInline);
+    /// ```
+    pub fn add_attr(&mut self, attr: FunctionAttribute) {
+        if let FunctionAttribute::Align(align) = attr
+            && !align.is_power_of_two()
+        {
+            panic!(
+                "The alignment of {} must be a power of two, but it is {align}",
+                self.name
+            );
+        }
+
+        let conflict = match attr {
+            FunctionAttribute::NoInline => Some(FunctionAttribute::AlwaysInline),
+            FunctionAttribute::AlwaysInline => Some(FunctionAttribute::NoInline),
+            _ => None,
+        };
+
+        if let Some(conflict) = conflict
+            && self.has_attr(&conflict)
+        {
+            panic!("{} can't be {attr:?} and {conflict:?}", self.name);
+        }
+
+        if !self.attrs.contains(&attr) {
+            self.attrs.push(attr);
+        }
+    }
+
+    /// Removes an attribute from the function
+    pub fn remove_attr(&mut self, attr: &FunctionAttribute) {
+        self.attrs.retain(|x| x != attr);
+    }
+
+    /// Returns if the function has the given attribute
+    pub fn has_attr(&self, attr: &FunctionAttribute) -> bool {
+        self.attrs.has_attr(attr)
+    }
+
+    /// Returns the attributes of the function
+    pub fn attrs(&self) -> &[FunctionAttribute] {
+        &self.attrs
+    }
+
+    /// Sets the metadata entry `key` to `value`
+    pub fn set_metadata(&mut self, key: &str, value: &str) {
+        self.metadata.insert(key.to_owned(), value.to_owned());
+    }
+
+    /// Returns the metadata entry for `key`
+    pub fn get_metadata(&self, key: &str) -> Option<&str> {
+        self.metadata.get(key).map(|x| x.as_str())
+    }
+
+    /// Returns all metadata entries of the function
+    pub fn metadata(&self) -> &BTreeMap<String, String> {
+        &self.metadata
+    }
+
+    /// Returns the ir of the function
+    pub fn ir(&self) -> &Vec<IrOperand> {
+        &self.ir
+    }
+
+    /// Returns the argument types of the function
+    pub fn args(&self) -> &Vec<TypeMetadata> {
+        &self.args
+    }
+
     /// Sets the return type of the function
     pub fn set_ret(&mut self, new: TypeMetadata) {
         self.ret = Some(new)
@@ -49,35 +338,85 @@ impl Function {
         }
     }
 
-    /// Adds two numbers
+    /// Adds two numbers (`try_add` checks the operands)
     pub fn add(&mut self, lhs: &IrOperand, rhs: &IrOperand) -> IrOperand {
         let node = IrNode::add(lhs, rhs);
-        self.ir.push(node.to_owned());
-        node
+        self.push(node)
     }
 
-    /// Subtracts two numbers
+    /// Subtracts two numbers (`try_sub` checks the operands)
     pub fn sub(&mut self, lhs: &IrOperand, rhs: &IrOperand) -> IrOperand {
         let node = IrNode::sub(lhs, rhs);
-        self.ir.push(node.to_owned());
-        node
+        self.push(node)
+    }
+
+    /// Calls the function with the given name, the output is only usable if `ret` is set
+    pub fn call(
+        &mut self,
+        callee: &str,
+        args: &[IrOperand],
+        ret: Option<TypeMetadata>,
+    ) -> IrOperand {
+        let node = IrNode::call(callee, args, ret);
+        self.push(node)
     }
 
     /// Returns the given constant
     pub fn ret(&mut self, op: &IrOperand) {
-        self.ir.push(IrNode::ret(op));
+        self.push(IrNode::ret(op));
     }
 
     /// Copys the value from one register to another
     pub fn copy(&mut self, op: &IrOperand) -> IrOperand {
         let node = IrNode::copy(op);
-        self.ir.push(node.to_owned());
-        node
+        self.push(node)
     }
 
     /// Gets the stack pointer
     pub fn get_sp(&mut self) -> IrOperand {
         let node = IrNode::get_stack_ptr();
+        self.push(node)
+    }
+
+    /// Sets the source location which is attached to all nodes created afterwards
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::codegen::TargetArch;
+    /// use jacob::ir::{Function, Module, TypeMetadata};
+    ///
+    /// let mut func = Function::new("add");
+    /// let x = func.add_arg(TypeMetadata::Int64);
+    /// let y = func.add_arg(TypeMetadata::Int64);
+    ///
+    /// func.set_loc("add.src", 1, 5);
+    /// let out = func.add(&x, &y);
+    /// func.set_loc("add.src", 2, 5);
+    /// func.ret(&out);
+    ///
+    /// let mut module = Module::new();
+    /// module.add_func(func);
+    ///
+    /// let asm = module.compile(TargetArch::Riscv64, false).asm();
+    /// assert!(asm.contains(".file 1 \"add.src\"\n"));
+    /// assert!(asm.contains(".loc 1 2 5\n"));
+    /// ```
+    pub fn set_loc(&mut self, file: &str, line: u32, column: u32) {
+        self.cur_loc = Some(SourceLoc::new(file, line, column));
+    }
+
+    /// Stops attaching a source location to newly created nodes
+    pub fn clear_loc(&mut self) {
+        self.cur_loc = None;
+    }
+
+    /// Adds the node to the ir and attaches the current source location
+    fn push(&mut self, node: IrOperand) -> IrOperand {
+        if let Some(loc) = &self.cur_loc {
+            node.force_node().borrow_mut().loc = Some(loc.to_owned());
+        }
+
+        self.nodes.insert(node.force_node());
         self.ir.push(node.to_owned());
         node
     }
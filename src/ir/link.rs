@@ -0,0 +1,192 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+
+use crate::ir::{Function, IrOperand, Module, visibility::Visibilty};
+
+/// An error which occured while linking two modules
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkError {
+    /// Both modules contain a strong (non weak) definition of the symbol
+    DuplicateDefinition(String),
+    /// The symbol is declared/defined with different argument or return types
+    SignatureMismatch(String),
+}
+
+impl Display for LinkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LinkError::DuplicateDefinition(name) => {
+                write!(f, "symbol `{name}` is defined multiple times")
+            }
+            LinkError::SignatureMismatch(name) => {
+                write!(f, "symbol `{name}` is used with different signatures")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LinkError {}
+
+impl Module {
+    /// Links the other module into this one
+    ///
+    /// Declarations are resolved against the definitions of the other module,
+    /// weak definitions are replaced by strong ones and `Internal` symbols which
+    /// collide with a symbol of the other module are renamed. The merged symbol gets
+    /// the most constraining symbol visibility of both. If an error is returned the
+    /// module is left unchanged
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::ir::{Function, Module, TypeMetadata, visibility::Visibilty};
+    ///
+    /// let mut main = Module::new();
+    /// let mut func = Function::new("main");
+    /// func.set_ret(TypeMetadata::Int64);
+    /// let out = func.call("answer", &[], Some(TypeMetadata::Int64));
+    /// func.ret(&out);
+    /// main.add_func(func);
+    ///
+    /// let mut decl = Function::new("answer");
+    /// decl.set_ret(TypeMetadata::Int64);
+    /// decl.set_visibility(Visibilty::External);
+    /// main.add_func(decl);
+    ///
+    /// let mut lib = Module::new();
+    /// let mut answer = Function::new("answer");
+    /// answer.set_ret(TypeMetadata::Int64);
+    /// answer.ret(&jacob::ir::IrOperand::ConstNum { num: 42, ty: TypeMetadata::Int64 });
+    /// lib.add_func(answer);
+    ///
+    /// main.link(lib).unwrap();
+    /// assert_eq!(main.funcs.len(), 2);
+    /// assert!(main.funcs.iter().all(|func| func.visibility() == Visibilty::Public));
+    ///
+    /// let out = jacob::interp::Interpreter::new().run_module(&main, "main", &[]);
+    /// assert_eq!(out, Ok(Some(jacob::interp::Value::int64(42))));
+    /// ```
+    pub fn link(&mut self, mut other: Module) -> Result<(), LinkError> {
+        let (self_renames, other_renames) = self.internal_collisions(&other);
+
+        let renamed = |renames: &HashMap<String, String>, name: &String| {
+            renames.get(name).unwrap_or(name).to_owned()
+        };
+
+        // first check that linking works, so that nothing is modified on an error
+        for func in &other.funcs {
+            let name = renamed(&other_renames, &func.name);
+
+            let Some(existing) = self
+                .funcs
+                .iter()
+                .find(|x| renamed(&self_renames, &x.name) == name)
+            else {
+                continue;
+            };
+
+            if existing.args != func.args || existing.ret != func.ret {
+                return Err(LinkError::SignatureMismatch(name));
+            }
+
+            let (old, new) = (existing.visibility, func.visibility);
+            if !old.is_declaration() && !new.is_declaration() && !old.is_weak() && !new.is_weak() {
+                return Err(LinkError::DuplicateDefinition(name));
+            }
+        }
+
+        for (old, new) in &self_renames {
+            Module::rename_func(&mut self.funcs, old, new);
+        }
+
+        for (old, new) in &other_renames {
+            Module::rename_func(&mut other.funcs, old, new);
+        }
+
+        for func in other.funcs {
+            let Some(existing) = self.funcs.iter_mut().find(|x| x.name == func.name) else {
+                self.funcs.push(func);
+                continue;
+            };
+
+            let (old, new) = (existing.visibility, func.visibility);
+            let symbol_visibility = existing.symbol_visibility.max(func.symbol_visibility);
+
+            let replace = if old.is_declaration() || new.is_declaration() {
+                old.is_declaration() && !new.is_declaration()
+            } else {
+                old.is_weak() && !new.is_weak()
+            };
+
+            if replace {
+                *existing = func;
+            }
+            existing.symbol_visibility = symbol_visibility;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the new names for the `Internal` functions which have the same name as a function
+    /// of the other module (the ones of `other` are renamed if both are internal)
+    fn internal_collisions(
+        &self,
+        other: &Module,
+    ) -> (HashMap<String, String>, HashMap<String, String>) {
+        let mut self_renames = HashMap::new();
+        let mut other_renames = HashMap::new();
+
+        let mut used: HashSet<String> = self
+            .funcs
+            .iter()
+            .chain(other.funcs.iter())
+            .map(|func| func.name.to_owned())
+            .collect();
+
+        for func in &other.funcs {
+            let Some(existing) = self.funcs.iter().find(|x| x.name == func.name) else {
+                continue;
+            };
+
+            let renames = if func.visibility == Visibilty::Internal {
+                &mut other_renames
+            } else if existing.visibility == Visibilty::Internal {
+                &mut self_renames
+            } else {
+                continue;
+            };
+
+            let mut index = 1;
+            let new_name = loop {
+                let candidate = format!("{}.{index}", func.name);
+                if !used.contains(&candidate) {
+                    break candidate;
+                }
+                index += 1;
+            };
+
+            used.insert(new_name.to_owned());
+            renames.insert(func.name.to_owned(), new_name);
+        }
+
+        (self_renames, other_renames)
+    }
+    /// Renames the function and all calls to it
+    fn rename_func(funcs: &mut [Function], old: &str, new: &str) {
+        for func in funcs {
+            if func.name == old {
+                func.name = new.to_owned();
+            }
+
+            for op in &func.ir {
+                if let IrOperand::Out(node) = op {
+                    let mut node = node.borrow_mut();
+                    if node.callee.as_deref() == Some(old) {
+                        node.callee = Some(new.to_owned());
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use std::sync::Arc;
+
+/// A position in the source code of the frontend
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SourceLoc {
+    /// The path of the source file
+    pub file: Arc<str>,
+    /// The line (starting at 1)
+    pub line: u32,
+    /// The column (starting at 1, 0 if unknown)
+    pub column: u32,
+}
+
+impl SourceLoc {
+    /// Creates a new source location
+    pub fn new(file: &str, line: u32, column: u32) -> Self {
+        Self {
+            file: Arc::from(file),
+            line,
+            column,
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use std::cell::RefCell;
+
+use crate::ir::{IrOpcode, IrOperand, TypeMetadata};
+
+/// A pattern which can be matched against an ir operand
+///
+/// Matchers look through `Drop` operands and are composed using the `m_*` functions
+///
+/// Example:
+/// ```rust
+/// use jacob::ir::{Function, IrOperand, Matcher, TypeMetadata, m_add, m_any, m_const};
+///
+/// let mut func = Function::new("noop");
+/// let x = func.add_arg(TypeMetadata::Int64);
+/// let zero = IrOperand::ConstNum { num: 0, ty: TypeMetadata::Int64 };
+/// let out = func.add(&x, &zero);
+///
+/// assert!(m_add(m_any(), m_const(0)).matches(&out));
+/// assert!(!m_add(m_const(0), m_any()).matches(&out));
+/// ```
+pub trait Matcher {
+    /// Returns if the operand matches the pattern
+    fn matches(&self, op: &IrOperand) -> bool;
+}
+
+/// Matches every operand
+#[derive(Debug, Clone, Copy)]
+pub struct AnyMatcher;
+
+impl Matcher for AnyMatcher {
+    fn matches(&self, _op: &IrOperand) -> bool {
+        true
+    }
+}
+
+/// Matches a constant number
+#[derive(Debug, Clone, Copy)]
+pub struct ConstMatcher(Option<usize>);
+
+impl Matcher for ConstMatcher {
+    fn matches(&self, op: &IrOperand) -> bool {
+        match (op.strip_drop(), self.0) {
+            (IrOperand::ConstNum { .. }, None) => true,
+            (IrOperand::ConstNum { num, .. }, Some(expected)) => *num == expected,
+            _ => false,
+        }
+    }
+}
+
+/// Matches an argument of the function
+#[derive(Debug, Clone, Copy)]
+pub struct ArgMatcher(Option<usize>);
+
+impl Matcher for ArgMatcher {
+    fn matches(&self, op: &IrOperand) -> bool {
+        match (op.strip_drop(), self.0) {
+            (IrOperand::Arg { .. }, None) => true,
+            (IrOperand::Arg { num, .. }, Some(expected)) => *num == expected,
+            _ => false,
+        }
+    }
+}
+
+/// Matches an operand of the given type (outputs without a value never match)
+#[derive(Debug, Clone, Copy)]
+pub struct TypeMatcher(TypeMetadata);
+
+impl Matcher for TypeMatcher {
+    fn matches(&self, op: &IrOperand) -> bool {
+        op.try_ty() == Some(self.0)
+    }
+}
+
+/// Matches the output of a node with the opcode and operands
+#[derive(Debug, Clone, Copy)]
+pub struct NodeMatcher<T> {
+    opcode: IrOpcode,
+    ops: T,
+}
+
+macro_rules! node_matcher {
+    ($($op:ident: $index:tt),*) => {
+        impl<$($op: Matcher),*> Matcher for NodeMatcher<($($op,)*)> {
+            fn matches(&self, op: &IrOperand) -> bool {
+                let Some(node) = op.node() else {
+                    return false;
+                };
+                let node = node.borrow();
+
+                node.opcode == self.opcode
+                    && node.ops.len() == [$($index),*].len()
+                    $(&& self.ops.$index.matches(&node.ops[$index]))*
+            }
+        }
+    };
+}
+
+node_matcher!(A: 0);
+node_matcher!(A: 0, B: 1);
+
+/// Matches the output of a commutative node with the operands in any order
+#[derive(Debug, Clone, Copy)]
+pub struct CommutativeMatcher<L, R>(NodeMatcher<(L, R)>);
+
+impl<L: Matcher, R: Matcher> Matcher for CommutativeMatcher<L, R> {
+    fn matches(&self, op: &IrOperand) -> bool {
+        let Some(node) = op.node() else {
+            return false;
+        };
+        let node = node.borrow();
+        let (lhs, rhs) = &self.0.ops;
+
+        node.opcode == self.0.opcode
+            && node.ops.len() == 2
+            && ((lhs.matches(&node.ops[0]) && rhs.matches(&node.ops[1]))
+                || (lhs.matches(&node.ops[1]) && rhs.matches(&node.ops[0])))
+    }
+}
+
+/// Matches if both matchers match
+#[derive(Debug, Clone, Copy)]
+pub struct AndMatcher<A, B>(A, B);
+
+impl<A: Matcher, B: Matcher> Matcher for AndMatcher<A, B> {
+    fn matches(&self, op: &IrOperand) -> bool {
+        self.0.matches(op) && self.1.matches(op)
+    }
+}
+
+/// Matches if one of the matchers matches
+#[derive(Debug, Clone, Copy)]
+pub struct OrMatcher<A, B>(A, B);
+
+impl<A: Matcher, B: Matcher> Matcher for OrMatcher<A, B> {
+    fn matches(&self, op: &IrOperand) -> bool {
+        self.0.matches(op) || self.1.matches(op)
+    }
+}
+
+/// Stores the matched operand into the slot if the inner matcher matches
+#[derive(Debug, Clone, Copy)]
+pub struct CaptureMatcher<'a, M> {
+    slot: &'a RefCell<Option<IrOperand>>,
+    inner: M,
+}
+
+impl<M: Matcher> Matcher for CaptureMatcher<'_, M> {
+    fn matches(&self, op: &IrOperand) -> bool {
+        if !self.inner.matches(op) {
+            return false;
+        }
+
+        *self.slot.borrow_mut() = Some(op.strip_drop().clone());
+        true
+    }
+}
+
+/// Matches every operand
+pub fn m_any() -> AnyMatcher {
+    AnyMatcher
+}
+
+/// Matches the constant number
+pub fn m_const(num: usize) -> ConstMatcher {
+    ConstMatcher(Some(num))
+}
+
+/// Matches every constant number
+pub fn m_const_any() -> ConstMatcher {
+    ConstMatcher(None)
+}
+
+/// Matches the argument with the number
+pub fn m_arg(num: usize) -> ArgMatcher {
+    ArgMatcher(Some(num))
+}
+
+/// Matches every argument
+pub fn m_arg_any() -> ArgMatcher {
+    ArgMatcher(None)
+}
+
+/// Matches operands of the type
+///
+/// Example:
+/// ```rust
+/// use jacob::ir::{Function, Matcher, TypeMetadata, m_ty};
+///
+/// let mut func = Function::new("main");
+/// let void = func.call("init", &[], None);
+///
+/// assert!(!m_ty(TypeMetadata::Int64).matches(&void));
+/// ```
+pub fn m_ty(ty: TypeMetadata) -> TypeMatcher {
+    TypeMatcher(ty)
+}
+
+/// Matches an addition
+pub fn m_add<L: Matcher, R: Matcher>(lhs: L, rhs: R) -> NodeMatcher<(L, R)> {
+    m_op2(IrOpcode::Add, lhs, rhs)
+}
+
+/// Matches an addition with the operands in any order
+pub fn m_c_add<L: Matcher, R: Matcher>(lhs: L, rhs: R) -> CommutativeMatcher<L, R> {
+    CommutativeMatcher(m_add(lhs, rhs))
+}
+
+/// Matches a subtraction
+pub fn m_sub<L: Matcher, R: Matcher>(lhs: L, rhs: R) -> NodeMatcher<(L, R)> {
+    m_op2(IrOpcode::Sub, lhs, rhs)
+}
+
+/// Matches a copy
+pub fn m_copy<M: Matcher>(op: M) -> NodeMatcher<(M,)> {
+    m_op1(IrOpcode::Copy, op)
+}
+
+/// Matches a node with the opcode and one operand
+pub fn m_op1<M: Matcher>(opcode: IrOpcode, op: M) -> NodeMatcher<(M,)> {
+    NodeMatcher { opcode, ops: (op,) }
+}
+
+/// Matches a node with the opcode and two operands
+pub fn m_op2<L: Matcher, R: Matcher>(opcode: IrOpcode, lhs: L, rhs: R) -> NodeMatcher<(L, R)> {
+    NodeMatcher {
+        opcode,
+        ops: (lhs, rhs),
+    }
+}
+
+/// Matches if both matchers match
+pub fn m_and<A: Matcher, B: Matcher>(a: A, b: B) -> AndMatcher<A, B> {
+    AndMatcher(a, b)
+}
+
+/// Matches if one of the matchers matches
+pub fn m_or<A: Matcher, B: Matcher>(a: A, b: B) -> OrMatcher<A, B> {
+    OrMatcher(a, b)
+}
+
+/// Stores the operand into the slot if the matcher matches
+///
+/// Example:
+/// ```rust
+/// use std::cell::RefCell;
+/// use jacob::ir::{Function, Matcher, TypeMetadata, m_any, m_capture, m_sub};
+///
+/// let mut func = Function::new("zero");
+/// let x = func.add_arg(TypeMetadata::Int64);
+/// let out = func.sub(&x, &x);
+///
+/// let lhs = RefCell::new(None);
+/// assert!(m_sub(m_capture(&lhs, m_any()), m_any()).matches(&out));
+/// assert_eq!(lhs.into_inner(), Some(x));
+/// ```
+pub fn m_capture<M: Matcher>(slot: &RefCell<Option<IrOperand>>, inner: M) -> CaptureMatcher<'_, M> {
+    CaptureMatcher { slot, inner }
+}
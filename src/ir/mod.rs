@@ -1,9 +1,25 @@
 //! Internal representation
 
+/// Function attributes
+pub mod attributes;
+/// Type checked ir building
+pub mod builder;
+/// Calling conventions
+pub mod callconv;
+/// Deep cloning of ir
+pub mod clone;
+/// Graphviz export
+pub mod dot;
 /// Function
 pub mod function;
 /// Instrincs
 pub mod instrinc;
+/// Module linking
+pub mod link;
+/// Source locations
+pub mod location;
+/// Ir pattern matching
+pub mod matcher;
 /// Compilation unit
 pub mod module;
 /// Ir nodes
@@ -12,12 +28,23 @@ pub mod node;
 pub mod operand;
 /// Types
 pub mod ty;
+/// Mutable variables
+pub mod var;
 /// Visibilty
 pub mod visibility;
 
+pub use attributes::*;
+pub use builder::*;
+pub use callconv::*;
+pub use clone::*;
+pub use dot::*;
 pub use function::*;
 pub use instrinc::*;
+pub use link::*;
+pub use location::*;
+pub use matcher::*;
 pub use module::*;
 pub use node::*;
 pub use operand::*;
 pub use ty::*;
+pub use var::*;
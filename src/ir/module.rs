@@ -1,8 +1,15 @@
-use std::{any::TypeId, collections::HashMap};
+use std::{
+    any::TypeId,
+    collections::HashMap,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
 
 use crate::{
-    codegen::{self, Compilation, FuncAsm, TargetArch},
-    ir::Function,
+    codegen::{self, ArchBackend, Compilation, FuncAsm, Target},
+    ir::{CallingConv, Function},
     opt::*,
 };
 
@@ -10,9 +17,10 @@ use crate::{
 pub struct Module {
     /// The functions of the module
     pub funcs: Vec<Function>,
-    registered_opts: HashMap<TypeId, Box<dyn Optimization>>,
+    registered_opts: HashMap<TypeId, Arc<dyn Optimization>>,
 
     opts_to_run: Vec<TypeId>,
+    opt_level: Option<OptLevel>,
 }
 
 impl Default for Module {
@@ -24,17 +32,20 @@ impl Default for Module {
 impl Module {
     /// Creates a new empty module
     pub fn new() -> Self {
-        let mut opts: HashMap<TypeId, Box<dyn Optimization>> = HashMap::new();
-
-        // ToDo: for each optimization which is newly implemented register it here like this
-        // opts.insert(TypeId::of::<AmazingOptimization>, Box::from( AmazingOptimization {} ))
-        opts.insert(TypeId::of::<Dce>(), Box::from(Dce {}));
-
-        Self {
+        let mut module = Self {
             funcs: Vec::new(),
-            registered_opts: opts,
+            registered_opts: HashMap::new(),
             opts_to_run: Vec::new(),
-        }
+            opt_level: None,
+        };
+
+        module.register_opt(ConstFold {});
+        module.register_opt(CopyProp {});
+        module.register_opt(Dce {});
+        module.register_opt(GlobalDce {});
+        module.register_opt(Gvn {});
+        module.register_opt(InstCombine::new());
+        module
     }
 
     /// Adds the function into the module
@@ -42,12 +53,23 @@ impl Module {
         self.funcs.push(func);
     }
 
+    /// Registers the optimization so it can be added to the queue with `add_opt`
+    ///
+    /// Registering an optimization of the same type again replaces the old one
+    pub fn register_opt<T: Optimization>(&mut self, opt: T) {
+        self.registered_opts
+            .insert(TypeId::of::<T>(), Arc::new(opt));
+    }
+
     /// Adds the given optimization to the queue
     pub fn add_opt<T: Optimization>(&mut self) {
         let id = TypeId::of::<T>();
 
         if !self.registered_opts.contains_key(&id) {
-            panic!("The optimization pass to add is not int ");
+            panic!(
+                "The optimization pass {} is not registered, register it with `register_opt` first",
+                std::any::type_name::<T>()
+            );
         }
 
         self.opts_to_run.push(id);
@@ -55,65 +77,278 @@ impl Module {
 
     /// Runs the optimizations in the queue
     pub fn run_opts(&mut self) {
-        for type_id in &self.opts_to_run {
-            let opt = self
-                .registered_opts
-                .get(type_id)
-                .expect("This here should never happen");
-
-            for func in &mut self.funcs {
-                opt.run(func);
-            }
+        let opts: Vec<Arc<dyn Optimization>> = self
+            .opts_to_run
+            .iter()
+            .map(|id| {
+                self.registered_opts
+                    .get(id)
+                    .expect("This here should never happen")
+                    .clone()
+            })
+            .collect();
+
+        let mut analyses = AnalysisManager::new();
 
-            // ToDo: opt.run_module(self);
+        for opt in opts {
+            if opt.run_module(self, &mut analyses) {
+                analyses.invalidate_module(&opt.preserved());
+            }
         }
     }
 
+    /// Runs the pipeline on the module and returns if it changed something
+    pub fn run_pipeline(&mut self, pipeline: &Pipeline) -> bool {
+        pipeline.run(self)
+    }
+
     /// Clears the optimization queue
     pub fn clear_opts(&mut self) {
         self.opts_to_run.clear();
     }
 
+    /// Sets the optimization level which is used for compiling the module
+    ///
+    /// Without a level only dead code is removed and the code generation strategies
+    /// of `O0` are used
+    pub fn set_opt_level(&mut self, level: OptLevel) {
+        self.opt_level = Some(level);
+    }
+
+    /// Returns the optimization level which is used for compiling the module (if one was set)
+    pub fn opt_level(&self) -> Option<OptLevel> {
+        self.opt_level
+    }
+
+    /// Runs the optimizations which happen before the compilation and returns the level
+    /// whose code generation strategies are used
+    fn optimize(&mut self) -> OptLevel {
+        self.dce();
+
+        match self.opt_level {
+            Some(level) => {
+                self.run_pipeline(&level.pipeline());
+                level
+            }
+            None => OptLevel::O0,
+        }
+    }
+
     /// Runs dead code elimination on the module without requiring the optimization pipeline
     pub(crate) fn dce(&mut self) {
-        for func in &mut self.funcs {
-            (Dce {}).run(func);
-        }
+        (Dce {}).run_module(self, &mut AnalysisManager::new());
     }
 
     /// Compiles the module
     ///
+    /// Removes dead code and runs the pass pipeline of the optimization level
+    /// (if one was set with `set_opt_level`) first
+    ///
     /// Args:
     ///  - `target` the target to compile to
     ///  - `rich_comments` should comments be inserted into the assembly code
     ///
     /// Example:
     /// ```rust
-    /// module.compile(codegen::TargetArch::X86, false);
+    /// use jacob::{codegen::TargetArch, ir::{Function, Module, TypeMetadata}, opt::OptLevel};
+    ///
+    /// let mut func = Function::new("add");
+    /// func.set_ret(TypeMetadata::Int64);
+    /// let x = func.add_arg(TypeMetadata::Int64);
+    /// let y = func.add_arg(TypeMetadata::Int64);
+    /// let sum = func.add(&x, &y);
+    /// let out = func.add(&sum, &x);
+    /// func.ret(&out);
+    ///
+    /// let mut module = Module::new();
+    /// module.add_func(func);
+    ///
+    /// let asm = module.compile(TargetArch::X86, false).asm();
+    /// assert!(asm.contains("\tlea rax, [rdi + rsi]\n\tlea rcx, [rax + rdi]\n\tmov rax, rcx\n"));
+    ///
+    /// module.set_opt_level(OptLevel::Os);
+    /// let asm = module.compile(TargetArch::X86, false).asm();
+    /// assert!(asm.contains("\tlea rax, [rdi + rsi]\n\tadd rax, rdi\n\tret"));
     /// ```
-    pub fn compile(&mut self, target: TargetArch, rich_comments: bool) -> Compilation {
-        self.dce();
+    pub fn compile(&mut self, target: impl Into<Target>, rich_comments: bool) -> Compilation {
+        let level = self.optimize();
 
-        let mut result = Compilation::new(target);
+        let target = target.into();
+        let mut result = Compilation::new(target.clone());
         let backend = target.backend();
+        let callconvs = self.callconvs();
 
-        for func in &self.funcs {
-            let mut asm = FuncAsm::new(func.name.to_owned(), &func.visibility);
+        for func in self.definitions() {
+            result.add(Module::compile_func(
+                func,
+                backend,
+                &callconvs,
+                level,
+                rich_comments,
+            ));
+        }
 
-            let mut dropper = codegen::Dropper::new(func.ir.clone());
-            dropper.run();
+        // ToDo: add public constants and that shit
 
-            let mut regalloc = codegen::RegAlloc::new(func.args.clone(), &*backend);
-            regalloc.run(dropper.get_ir());
+        result
+    }
 
-            let mut inst = codegen::InstSelector::new(regalloc.get_ir(), &*backend, rich_comments);
-            inst.run(&mut asm);
+    /// Compiles the module using multiple worker threads
+    ///
+    /// Each function is compiled on its own by one of the workers. The functions
+    /// in the returned `Compilation` are in the same order as in the module, so
+    /// the result is the same as the one of `compile`
+    ///
+    /// Args:
+    ///  - `target` the target to compile to
+    ///  - `rich_comments` should comments be inserted into the assembly code
+    ///  - `threads` the number of worker threads (`0` uses the available parallelism)
+    ///
+    /// Example:
+    /// ```rust
+    /// # use jacob::{codegen, ir::Module};
+    /// # let mut module = Module::new();
+    /// module.compile_parallel(codegen::TargetArch::X86, false, 0);
+    /// ```
+    pub fn compile_parallel(
+        &mut self,
+        target: impl Into<Target>,
+        rich_comments: bool,
+        threads: usize,
+    ) -> Compilation {
+        let level = self.optimize();
 
-            result.add(asm);
+        let target = target.into();
+
+        let funcs: Vec<&Function> = self.definitions().collect();
+
+        let threads = match threads {
+            0 => std::thread::available_parallelism().map_or(1, |x| x.get()),
+            threads => threads,
         }
+        .min(funcs.len())
+        .max(1);
 
-        // ToDo: add public constants and that shit
+        let next = AtomicUsize::new(0);
+        let funcs = &funcs;
+        let callconvs = &self.callconvs();
+
+        let mut compiled: Vec<(usize, FuncAsm)> = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let backend = target.backend();
+                        let mut out = Vec::new();
+
+                        loop {
+                            let index = next.fetch_add(1, Ordering::Relaxed);
+                            let Some(func) = funcs.get(index) else {
+                                break;
+                            };
+
+                            out.push((
+                                index,
+                                Module::compile_func(
+                                    func,
+                                    backend,
+                                    callconvs,
+                                    level,
+                                    rich_comments,
+                                ),
+                            ));
+                        }
+
+                        out
+                    })
+                })
+                .collect();
+
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().expect("compilation worker panicked"))
+                .collect()
+        });
+
+        compiled.sort_by_key(|(index, _)| *index);
+
+        let mut result = Compilation::new(target);
+        for (_, asm) in compiled {
+            result.add(asm);
+        }
 
         result
     }
+
+    /// Returns the functions which are defined (and not only declared) in the module
+    fn definitions(&self) -> impl Iterator<Item = &Function> {
+        self.funcs
+            .iter()
+            .filter(|func| !func.visibility.is_declaration())
+    }
+
+    /// Returns the calling conventions of all functions in the module
+    fn callconvs(&self) -> HashMap<String, CallingConv> {
+        self.funcs
+            .iter()
+            .map(|func| (func.name.to_owned(), func.callconv))
+            .collect()
+    }
+
+    /// Runs the code generation pipeline for a single function
+    fn compile_func(
+        func: &Function,
+        backend: &dyn ArchBackend,
+        callconvs: &HashMap<String, CallingConv>,
+        level: OptLevel,
+        rich_comments: bool,
+    ) -> FuncAsm {
+        if !backend.supports_callconv(func.callconv) {
+            panic!(
+                "The calling convention {:?} of {} is not supported by {}",
+                func.callconv,
+                func.name,
+                backend.name()
+            );
+        }
+
+        let mut asm = FuncAsm::new(func.name.to_owned(), &func.visibility);
+        asm.symbol_visibility = func.symbol_visibility;
+        asm.attrs = func.attrs.clone();
+
+        let mut analyses = AnalysisManager::new();
+
+        let mut dropper = codegen::Dropper::new(func);
+        dropper.run(&mut analyses);
+
+        let mut regalloc =
+            codegen::RegAlloc::with_callconv(func.args.clone(), func.callconv, backend)
+                .with_callconvs(callconvs)
+                .with_strategy(level.regalloc_strategy())
+                .with_coalescing(level.coalesces_copies());
+        regalloc.run(dropper.get_ir());
+
+        let mut inst = codegen::InstSelector::new(regalloc.get_ir(), backend, rich_comments)
+            .with_callconvs(callconvs)
+            .with_strategy(level.isel_strategy());
+        inst.run(&mut asm);
+
+        asm.stack_slots = regalloc.stack_slots();
+        asm.frame_size = regalloc.frame_size();
+        asm.saved_regs = regalloc.saved_regs().to_vec();
+        asm.link_slot = regalloc.link_slot();
+        asm.args = regalloc.args().clone();
+        asm.arg_ranges = regalloc
+            .arg_ranges()
+            .iter()
+            .map(|ranges| {
+                ranges
+                    .iter()
+                    .map(|range| inst.inst_range(range, &asm))
+                    .collect()
+            })
+            .collect();
+        asm.ret = func.ret;
+
+        asm
+    }
 }
@@ -1,10 +1,12 @@
 use std::{
-    cell::RefCell,
+    collections::HashSet,
     hash::{Hash, Hasher},
-    rc::Rc,
+    sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
 };
 
-use crate::ir::{InstrincSettings, operand::IrOperand, ty::TypeMetadata};
+use crate::ir::{
+    FunctionAttribute, InstrincSettings, SourceLoc, operand::IrOperand, ty::TypeMetadata,
+};
 
 /// The opcode of the node
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -19,6 +21,8 @@ pub enum IrOpcode {
     Copy,
     /// Calls an instrinc
     InstrincCall(InstrincSettings),
+    /// Calls another function (the callee is stored in the node)
+    Call,
 }
 
 /// An ir node
@@ -28,6 +32,9 @@ pub struct IrNode {
     pub(crate) ops: Vec<IrOperand>,
     pub(crate) has_out: bool,
     pub(crate) ty: Option<TypeMetadata>,
+    pub(crate) callee: Option<String>,
+    pub(crate) call_attrs: Vec<FunctionAttribute>,
+    pub(crate) loc: Option<SourceLoc>,
 }
 
 macro_rules! op2 {
@@ -35,12 +42,15 @@ macro_rules! op2 {
         /// Creates the node
         pub fn $name(lhs: &IrOperand, rhs: &IrOperand) -> IrOperand {
             let ty = lhs.get_ty();
-            IrOperand::Out(Rc::new(RefCell::new(IrNode {
+            IrOperand::Out(NodeRef::new(IrNode {
                 opcode: $opcode,
                 ops: vec![lhs.clone(), rhs.clone()],
                 has_out: true,
                 ty: Some(ty),
-            })))
+                callee: None,
+                call_attrs: Vec::new(),
+                loc: None,
+            }))
         }
     };
 }
@@ -49,12 +59,15 @@ macro_rules! op1 {
         /// Creates the node
         pub fn $name(op: &IrOperand) -> IrOperand {
             let ty = op.get_ty();
-            IrOperand::Out(Rc::new(RefCell::new(IrNode {
+            IrOperand::Out(NodeRef::new(IrNode {
                 opcode: $opcode,
                 ops: vec![op.clone()],
                 has_out: $out,
                 ty: Some(ty),
-            })))
+                callee: None,
+                call_attrs: Vec::new(),
+                loc: None,
+            }))
         }
     };
 }
@@ -67,12 +80,28 @@ impl IrNode {
 
     /// Creates a new get stack pointer instrinc
     pub fn get_stack_ptr() -> IrOperand {
-        IrOperand::Out(Rc::new(RefCell::new(IrNode {
+        IrOperand::Out(NodeRef::new(IrNode {
             opcode: IrOpcode::InstrincCall(InstrincSettings::get_stack_ptr()),
             ops: Vec::new(),
             has_out: true,
             ty: Some(TypeMetadata::Int64),
-        })))
+            callee: None,
+            call_attrs: Vec::new(),
+            loc: None,
+        }))
+    }
+
+    /// Creates a new call to the function with the given name
+    pub fn call(callee: &str, args: &[IrOperand], ret: Option<TypeMetadata>) -> IrOperand {
+        IrOperand::Out(NodeRef::new(IrNode {
+            opcode: IrOpcode::Call,
+            ops: args.to_vec(),
+            has_out: ret.is_some(),
+            ty: ret,
+            callee: Some(callee.to_owned()),
+            call_attrs: Vec::new(),
+            loc: None,
+        }))
     }
 
     /// Returns the type of the node
@@ -80,6 +109,21 @@ impl IrNode {
         self.ty
     }
 
+    /// Returns the opcode of the node
+    pub fn opcode(&self) -> IrOpcode {
+        self.opcode
+    }
+
+    /// Returns the operands of the node
+    pub fn ops(&self) -> &[IrOperand] {
+        &self.ops
+    }
+
+    /// Returns if the node produces a value
+    pub fn has_out(&self) -> bool {
+        self.has_out
+    }
+
     /// Returns if the instruction has the `add` opcode
     pub fn is_add(&self) -> bool {
         matches!(self.opcode, IrOpcode::Add)
@@ -100,19 +144,59 @@ impl IrNode {
         matches!(self.opcode, IrOpcode::Copy)
     }
 
+    /// Returns the source location the node was created for
+    pub fn loc(&self) -> Option<&SourceLoc> {
+        self.loc.as_ref()
+    }
+
+    /// Returns if the instruction is a call
+    pub fn is_call(&self) -> bool {
+        matches!(self.opcode, IrOpcode::Call)
+    }
+
+    /// Returns the name of the called function (if the node is a call)
+    pub fn callee(&self) -> Option<&str> {
+        self.callee.as_deref()
+    }
+
+    /// Returns the attributes of the called function which describe the effects of the call
+    /// (calls created with `Function::try_call` have them)
+    pub fn call_attrs(&self) -> &[FunctionAttribute] {
+        &self.call_attrs
+    }
+
+    /// Returns if the call never returns
+    pub fn is_noreturn_call(&self) -> bool {
+        self.is_call() && self.call_attrs.contains(&FunctionAttribute::NoReturn)
+    }
+
     /// Returns if the instruction is an instrinc
     pub fn is_instrinc(&self) -> bool {
         matches!(self.opcode, IrOpcode::InstrincCall(_))
     }
 
+    /// Returns if the node does more than computing its output, so it must not be removed
+    /// even if the output isn't used (instrincs are conservatively treated as such)
+    ///
+    /// Calls only have none if the callee is `ReadNone` (and returns)
+    pub fn has_side_effects(&self) -> bool {
+        match self.opcode {
+            IrOpcode::Call => {
+                !self.call_attrs.contains(&FunctionAttribute::ReadNone) || self.is_noreturn_call()
+            }
+            IrOpcode::Ret | IrOpcode::InstrincCall(_) => true,
+            IrOpcode::Add | IrOpcode::Sub | IrOpcode::Copy => false,
+        }
+    }
+
     /// Gets the first operand (be carful, if there are no operands, this function will panic!)
     pub fn get_lhs(&self) -> &IrOperand {
         self.ops.first().unwrap()
     }
 
-    /// Gets the second operand (be carful, if there are no operands, this function will panic!)
+    /// Gets the second operand (be carful, if there are less than 2 operands, this function will panic!)
     pub fn get_rhs(&self) -> &IrOperand {
-        self.ops.get(2).unwrap()
+        self.ops.get(1).unwrap()
     }
 
     /// Returns if the node has 2 ops
@@ -132,3 +216,130 @@ impl IrNode {
         hasher.finish()
     }
 }
+
+impl Drop for IrNode {
+    /// Drops the nodes which are only used by this one with an explicit stack,
+    /// so dropping long chains of nodes doesn't overflow the stack
+    fn drop(&mut self) {
+        let mut ops = std::mem::take(&mut self.ops);
+
+        while let Some(op) = ops.pop() {
+            match op {
+                IrOperand::Out(node) => {
+                    if let Some(lock) = Arc::into_inner(node.0) {
+                        let mut node = lock.into_inner().unwrap_or_else(|err| err.into_inner());
+                        ops.append(&mut node.ops);
+                    }
+                }
+                IrOperand::Drop(op) => ops.extend(Arc::into_inner(op)),
+                IrOperand::Arg { .. } | IrOperand::ConstNum { .. } => {}
+            }
+        }
+    }
+}
+
+/// A shared, thread safe handle to an ir node
+///
+/// Operands which use the output of a node all point to the same `NodeRef`,
+/// so modifying the node through one of them is visible to all users
+#[derive(Clone)]
+pub struct NodeRef(Arc<RwLock<IrNode>>);
+
+impl NodeRef {
+    /// Wraps the node into a new handle
+    pub fn new(node: IrNode) -> Self {
+        Self(Arc::new(RwLock::new(node)))
+    }
+
+    /// Immutably borrows the node
+    pub fn borrow(&self) -> RwLockReadGuard<'_, IrNode> {
+        self.0.read().expect("ir node lock is poisoned")
+    }
+
+    /// Mutably borrows the node
+    pub fn borrow_mut(&self) -> RwLockWriteGuard<'_, IrNode> {
+        self.0.write().expect("ir node lock is poisoned")
+    }
+
+    /// Returns if both handles point to the same node
+    pub fn ptr_eq(&self, other: &NodeRef) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+
+    /// Returns an id which is unique for the node as long as it is alive
+    pub fn id(&self) -> usize {
+        Arc::as_ptr(&self.0) as usize
+    }
+}
+
+impl PartialEq for NodeRef {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other) || *self.borrow() == *other.borrow()
+    }
+}
+
+impl Eq for NodeRef {}
+
+impl std::fmt::Debug for NodeRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.borrow().fmt(f)
+    }
+}
+
+/// Returns all nodes of the ir in evaluation order (the operands of a node come
+/// before the node itself), every node is only returned once
+///
+/// The nodes are walked with an explicit stack, so long chains of nodes don't
+/// overflow the stack
+///
+/// Example:
+/// ```rust
+/// use jacob::ir::{Function, IrOperand, TypeMetadata, walk_nodes};
+///
+/// let mut func = Function::new("long");
+/// let mut value = func.add_arg(TypeMetadata::Int64);
+/// let one = IrOperand::ConstNum { num: 1, ty: TypeMetadata::Int64 };
+///
+/// for _ in 0..100_000 {
+///     value = func.add(&value, &one);
+/// }
+///
+/// let nodes = walk_nodes(std::slice::from_ref(&value));
+/// assert_eq!(nodes.len(), 100_000);
+/// assert!(nodes.last().unwrap().ptr_eq(value.force_node()));
+/// ```
+pub fn walk_nodes(ir: &[IrOperand]) -> Vec<NodeRef> {
+    let mut seen = HashSet::new();
+    let mut out = Vec::new();
+
+    // the flag is set once the operands of the node were pushed
+    let mut stack: Vec<(NodeRef, bool)> = Vec::new();
+
+    for op in ir {
+        stack.extend(op.node().map(|node| (node.to_owned(), false)));
+
+        while let Some((node, expanded)) = stack.pop() {
+            if expanded {
+                out.push(node);
+                continue;
+            }
+
+            if !seen.insert(node.id()) {
+                continue;
+            }
+
+            stack.push((node.to_owned(), true));
+
+            // the operands are pushed in reverse, so the first one is walked first
+            for op in node.borrow().ops().iter().rev() {
+                if let Some(used) = op.node()
+                    && !seen.contains(&used.id())
+                {
+                    stack.push((used.to_owned(), false));
+                }
+            }
+        }
+    }
+
+    out
+}
@@ -1,10 +1,9 @@
 use std::{
-    cell::RefCell,
     hash::{Hash, Hasher},
-    rc::Rc,
+    sync::Arc,
 };
 
-use crate::ir::{node::IrNode, ty::TypeMetadata};
+use crate::ir::{node::NodeRef, ty::TypeMetadata};
 
 /// An ir operand
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -25,20 +24,25 @@ pub enum IrOperand {
     },
 
     /// The output of a previous instruction
-    Out(Rc<RefCell<IrNode>>),
+    Out(NodeRef),
 
     /// Drop the used resource for the output of the given instruction
-    Drop(Rc<IrOperand>),
+    Drop(Arc<IrOperand>),
 }
 
 impl IrOperand {
     /// Returns the type
     pub fn get_ty(&self) -> TypeMetadata {
+        self.try_ty().expect("Expected type")
+    }
+
+    /// Returns the type (or `None` if it's the output of a node which doesn't produce a value)
+    pub fn try_ty(&self) -> Option<TypeMetadata> {
         match self {
-            IrOperand::Arg { num: _, ty } => *ty,
-            IrOperand::ConstNum { num: _, ty } => *ty,
-            IrOperand::Out(ref_cell) => ref_cell.borrow().get_ty().expect("Expected type"),
-            IrOperand::Drop(ref_cell) => ref_cell.get_ty(),
+            IrOperand::Arg { num: _, ty } => Some(*ty),
+            IrOperand::ConstNum { num: _, ty } => Some(*ty),
+            IrOperand::Out(ref_cell) => ref_cell.borrow().get_ty(),
+            IrOperand::Drop(ref_cell) => ref_cell.try_ty(),
         }
     }
 
@@ -58,7 +62,7 @@ impl IrOperand {
     }
 
     /// Force gets a node (if it's something else e.g: an arg it just panics)
-    pub fn force_node(&self) -> &Rc<RefCell<IrNode>> {
+    pub fn force_node(&self) -> &NodeRef {
         match self {
             IrOperand::Out(ref_cell) => ref_cell,
             _ => panic!(),
@@ -66,13 +70,45 @@ impl IrOperand {
     }
 
     /// Force gets the internal operand for the drop variant
-    pub fn force_op(&self) -> &Rc<IrOperand> {
+    pub fn force_op(&self) -> &Arc<IrOperand> {
         match self {
             IrOperand::Drop(rc) => rc,
             _ => panic!(),
         }
     }
 
+    /// Returns the operand without the surrounding drops
+    pub fn strip_drop(&self) -> &IrOperand {
+        match self {
+            IrOperand::Drop(op) => op.strip_drop(),
+            op => op,
+        }
+    }
+
+    /// Returns the node which produces the operand (looks through drops)
+    pub fn node(&self) -> Option<&NodeRef> {
+        match self.strip_drop() {
+            IrOperand::Out(node) => Some(node),
+            _ => None,
+        }
+    }
+
+    /// Returns the number if the operand is a constant (looks through drops)
+    pub fn const_num(&self) -> Option<usize> {
+        match self.strip_drop() {
+            IrOperand::ConstNum { num, .. } => Some(*num),
+            _ => None,
+        }
+    }
+
+    /// Returns the argument number if the operand is an argument (looks through drops)
+    pub fn arg_num(&self) -> Option<usize> {
+        match self.strip_drop() {
+            IrOperand::Arg { num, .. } => Some(*num),
+            _ => None,
+        }
+    }
+
     /// Force gets the argument data fields for the arg variant
     pub fn force_arg(&self) -> (usize, TypeMetadata) {
         match self {
@@ -85,6 +121,22 @@ impl IrOperand {
 impl Hash for IrOperand {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         core::mem::discriminant(self).hash(state);
+
+        // nodes are only hashed shallowly, as hashing the whole tree is exponential
+        // for shared operands (equal nodes still hash equally)
+        match self {
+            IrOperand::Arg { num, ty } | IrOperand::ConstNum { num, ty } => {
+                num.hash(state);
+                ty.hash(state);
+            }
+            IrOperand::Out(node) => {
+                let node = node.borrow();
+                node.opcode.hash(state);
+                node.ty.hash(state);
+                node.ops.len().hash(state);
+            }
+            IrOperand::Drop(op) => op.hash(state),
+        }
     }
 }
 
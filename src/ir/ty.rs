@@ -4,3 +4,19 @@ pub enum TypeMetadata {
     /// Signed 64 bit intenger
     Int64,
 }
+
+impl TypeMetadata {
+    /// Returns the size of the type in bits
+    pub fn bitwidth(&self) -> u32 {
+        match self {
+            TypeMetadata::Int64 => 64,
+        }
+    }
+
+    /// Returns if the type is a signed integer
+    pub fn signed(&self) -> bool {
+        match self {
+            TypeMetadata::Int64 => true,
+        }
+    }
+}
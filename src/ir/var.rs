@@ -0,0 +1,155 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::ir::{BuildError, Function, IrOperand, TypeMetadata};
+
+/// A mutable local variable of a function
+///
+/// Variables are converted into ssa values while the ir is built: every use is
+/// replaced with the value of the last definition
+///
+/// The ir has no blocks or branches, so the last definition is the only one which
+/// reaches a use. Merging definitions of different paths (with phis or block
+/// arguments) is not supported, because there are no such paths
+///
+/// A variable can only be used in the function which declared it (and copies of it)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Variable {
+    pub(crate) func: usize,
+    pub(crate) num: usize,
+}
+
+/// Returns a new id which identifies the variables of a function
+pub(crate) fn next_func_id() -> usize {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    NEXT.fetch_add(1, Ordering::Relaxed)
+}
+
+impl Function {
+    /// Declares a new mutable variable of the type
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::interp::{Interpreter, Value};
+    /// use jacob::ir::{Function, IrOperand, TypeMetadata};
+    ///
+    /// let mut func = Function::new("counter");
+    /// func.set_ret(TypeMetadata::Int64);
+    /// let start = func.add_arg(TypeMetadata::Int64);
+    /// let one = IrOperand::ConstNum { num: 1, ty: TypeMetadata::Int64 };
+    ///
+    /// let i = func.declare_var(TypeMetadata::Int64);
+    /// func.def_var(i, &start);
+    ///
+    /// for _ in 0..3 {
+    ///     let old = func.use_var(i);
+    ///     let new = func.add(&old, &one);
+    ///     func.def_var(i, &new);
+    /// }
+    ///
+    /// let out = func.use_var(i);
+    /// func.ret(&out);
+    ///
+    /// let out = Interpreter::new().run(&func, &[Value::int64(4)]).unwrap();
+    /// assert_eq!(out, Some(Value::int64(7)));
+    /// ```
+    pub fn declare_var(&mut self, ty: TypeMetadata) -> Variable {
+        self.vars.push(ty);
+
+        Variable {
+            func: self.id,
+            num: self.vars.len() - 1,
+        }
+    }
+
+    /// Assigns the value to the variable
+    ///
+    /// Panics if `try_def_var` would return an error
+    pub fn def_var(&mut self, var: Variable, value: &IrOperand) {
+        if let Err(err) = self.try_def_var(var, value) {
+            panic!("Can't define {var:?} in {}: {err}", self.name);
+        }
+    }
+
+    /// Returns the current value of the variable
+    ///
+    /// Panics if `try_use_var` would return an error
+    pub fn use_var(&self, var: Variable) -> IrOperand {
+        match self.try_use_var(var) {
+            Ok(value) => value,
+            Err(err) => panic!("Can't use {var:?} in {}: {err}", self.name),
+        }
+    }
+
+    /// Returns the type of the variable
+    ///
+    /// Panics if the variable isn't declared in the function
+    pub fn var_ty(&self, var: Variable) -> TypeMetadata {
+        match self.try_var_ty(var) {
+            Ok(ty) => ty,
+            Err(err) => panic!("{err} ({var:?} in {})", self.name),
+        }
+    }
+
+    /// Same as `def_var` but checks that the variable is declared in the function
+    /// and that the value belongs to the function and has the type of the variable
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::ir::{BuildError, Function, TypeMetadata};
+    ///
+    /// let mut other = Function::new("other");
+    /// let var = other.declare_var(TypeMetadata::Int64);
+    /// let y = other.add_arg(TypeMetadata::Int64);
+    /// let sum = other.add(&y, &y);
+    ///
+    /// let mut func = Function::new("func");
+    /// let x = func.add_arg(TypeMetadata::Int64);
+    /// let i = func.declare_var(TypeMetadata::Int64);
+    ///
+    /// assert_eq!(func.try_def_var(var, &x), Err(BuildError::UnknownVar(var)));
+    /// assert_eq!(func.try_def_var(i, &sum), Err(BuildError::ForeignValue));
+    /// assert_eq!(func.try_def_var(i, &x), Ok(()));
+    /// ```
+    pub fn try_def_var(&mut self, var: Variable, value: &IrOperand) -> Result<(), BuildError> {
+        let ty = self.try_var_ty(var)?;
+        self.check_op(value)?;
+        Function::check_ty(ty, value)?;
+
+        self.var_defs.insert(var, value.to_owned());
+        Ok(())
+    }
+
+    /// Same as `use_var` but returns an error if the variable isn't declared in the
+    /// function or has no definition yet
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::ir::{BuildError, Function, TypeMetadata};
+    ///
+    /// let mut func = Function::new("func");
+    /// let i = func.declare_var(TypeMetadata::Int64);
+    ///
+    /// assert_eq!(func.try_use_var(i), Err(BuildError::UndefinedVar(i)));
+    /// ```
+    pub fn try_use_var(&self, var: Variable) -> Result<IrOperand, BuildError> {
+        self.try_var_ty(var)?;
+
+        // the ir is straight line code, so the last definition is the only reaching one
+        self.var_defs
+            .get(&var)
+            .cloned()
+            .ok_or(BuildError::UndefinedVar(var))
+    }
+
+    /// Returns the type of the variable or an error if it isn't declared in the function
+    pub fn try_var_ty(&self, var: Variable) -> Result<TypeMetadata, BuildError> {
+        if var.func != self.id {
+            return Err(BuildError::UnknownVar(var));
+        }
+
+        self.vars
+            .get(var.num)
+            .copied()
+            .ok_or(BuildError::UnknownVar(var))
+    }
+}
@@ -1,8 +1,47 @@
-/// An enum which defines the visibility
+/// An enum which defines the visibility and linkage of a symbol
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Visibilty {
     /// The code is only visible in the module and not publicly visible and usable from other modules/object files
     Internal,
     /// The code is visibila and callable/usable from all linked modules/object files
     Public,
+    /// Like `Public` but another strong definition of the symbol replaces this one while linking
+    Weak,
+    /// Like `Weak` but the definition is placed in its own COMDAT group, so the linker keeps
+    /// exactly one copy of it (used for header-only style generics and inline functions)
+    LinkOnce,
+    /// The symbol is only declared and defined in another module/object file
+    External,
+}
+
+impl Visibilty {
+    /// Returns if the symbol is visible outside of the module
+    pub fn is_exported(&self) -> bool {
+        !matches!(self, Visibilty::Internal)
+    }
+
+    /// Returns if the symbol is only a declaration without a definition
+    pub fn is_declaration(&self) -> bool {
+        matches!(self, Visibilty::External)
+    }
+
+    /// Returns if a definition with this visibility can be replaced by another definition
+    pub fn is_weak(&self) -> bool {
+        matches!(self, Visibilty::Weak | Visibilty::LinkOnce)
+    }
+}
+
+/// The visibility of an exported symbol outside of the shared library it is linked into
+///
+/// It is independent of the linkage, so e.g. a weak symbol can be hidden. The variants are
+/// ordered from the least to the most constraining one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum SymbolVisibility {
+    /// The symbol is exported from the shared library and can be preempted
+    #[default]
+    Default,
+    /// The symbol is exported but references from inside the shared library can't be preempted
+    Protected,
+    /// The symbol is not exported from the shared library it is linked into
+    Hidden,
 }
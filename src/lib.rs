@@ -3,9 +3,14 @@
 #![forbid(missing_docs)]
 #![forbid(non_snake_case)]
 
+// the `backend!` and `patterns!` macros emit `::jacob::...` paths, so they
+// need to resolve inside of this crate too
+extern crate self as jacob;
+
 pub mod aarch64;
 pub mod codegen;
 pub mod decompile;
+pub mod interp;
 pub mod ir;
 pub mod opt;
 pub mod riscv64;
@@ -0,0 +1,66 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    ir::Module,
+    opt::{ModuleAnalysis, walk_nodes},
+};
+
+/// Which functions of a module call which functions
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CallGraph {
+    callees: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl CallGraph {
+    /// Returns the functions which are called by the function
+    pub fn callees(&self, name: &str) -> impl Iterator<Item = &str> {
+        self.callees
+            .get(name)
+            .into_iter()
+            .flatten()
+            .map(|x| x.as_str())
+    }
+
+    /// Returns the functions which call the function
+    pub fn callers(&self, name: &str) -> impl Iterator<Item = &str> {
+        self.callees
+            .iter()
+            .filter(move |(_, callees)| callees.contains(name))
+            .map(|(caller, _)| caller.as_str())
+    }
+
+    /// Returns all functions which are reachable through calls from the roots
+    /// (including the roots)
+    pub fn reachable<'a>(&self, roots: impl IntoIterator<Item = &'a str>) -> BTreeSet<String> {
+        let mut reached = BTreeSet::new();
+        let mut todo: Vec<String> = roots.into_iter().map(|x| x.to_owned()).collect();
+
+        while let Some(name) = todo.pop() {
+            if !reached.insert(name.to_owned()) {
+                continue;
+            }
+
+            todo.extend(self.callees(&name).map(|x| x.to_owned()));
+        }
+
+        reached
+    }
+}
+
+impl ModuleAnalysis for CallGraph {
+    fn compute(module: &Module) -> Self {
+        let mut callees = BTreeMap::new();
+
+        for func in &module.funcs {
+            let called: &mut BTreeSet<String> = callees.entry(func.name.to_owned()).or_default();
+
+            for node in walk_nodes(func.ir()) {
+                if let Some(callee) = node.borrow().callee() {
+                    called.insert(callee.to_owned());
+                }
+            }
+        }
+
+        Self { callees }
+    }
+}
@@ -0,0 +1,47 @@
+use std::collections::HashMap;
+
+use crate::{
+    ir::{Function, NodeRef},
+    opt::{FunctionAnalysis, walk_nodes},
+};
+
+/// The dominator tree of the nodes of a function
+///
+/// The ir of a function is a single block, so every node is dominated by all nodes
+/// which are evaluated before it
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Dominators {
+    order: Vec<NodeRef>,
+    pos: HashMap<usize, usize>,
+}
+
+impl Dominators {
+    /// Returns if `a` dominates `b` (every node dominates itself)
+    ///
+    /// Nodes which were created after the analysis was computed don't dominate anything
+    pub fn dominates(&self, a: &NodeRef, b: &NodeRef) -> bool {
+        match (self.pos.get(&a.id()), self.pos.get(&b.id())) {
+            (Some(a), Some(b)) => a <= b,
+            _ => false,
+        }
+    }
+
+    /// Returns the immediate dominator of the node
+    pub fn idom(&self, node: &NodeRef) -> Option<&NodeRef> {
+        let pos = *self.pos.get(&node.id())?;
+        pos.checked_sub(1).map(|pos| &self.order[pos])
+    }
+}
+
+impl FunctionAnalysis for Dominators {
+    fn compute(func: &Function) -> Self {
+        let order = walk_nodes(func.ir());
+        let pos = order
+            .iter()
+            .enumerate()
+            .map(|(pos, node)| (node.id(), pos))
+            .collect();
+
+        Self { order, pos }
+    }
+}
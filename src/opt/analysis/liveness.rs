@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+
+use crate::{
+    ir::{Function, IrOperand, NodeRef},
+    opt::{FunctionAnalysis, walk_nodes},
+};
+
+/// The live ranges of the node outputs
+///
+/// Positions are indices into the evaluation order (see `walk_nodes`). A value is
+/// live from the node which defines it up to its last use
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Liveness {
+    ranges: HashMap<usize, (usize, usize)>,
+}
+
+impl Liveness {
+    /// Computes the live ranges of the ir
+    pub fn of_ir(ir: &[IrOperand]) -> Self {
+        let mut ranges = HashMap::new();
+
+        for (pos, node) in walk_nodes(ir).iter().enumerate() {
+            ranges.insert(node.id(), (pos, pos));
+
+            for op in node.borrow().ops() {
+                if let Some(used) = op.node()
+                    && let Some(range) = ranges.get_mut(&used.id())
+                {
+                    range.1 = pos;
+                }
+            }
+        }
+
+        Self { ranges }
+    }
+
+    /// Returns the position of the definition and the last use of the node
+    pub fn range(&self, node: &NodeRef) -> Option<(usize, usize)> {
+        self.ranges.get(&node.id()).copied()
+    }
+
+    /// Returns if the output of the node is still needed after the position
+    pub fn is_live_after(&self, node: &NodeRef, pos: usize) -> bool {
+        self.range(node)
+            .is_some_and(|(def, last_use)| def <= pos && pos < last_use)
+    }
+}
+
+impl FunctionAnalysis for Liveness {
+    fn compute(func: &Function) -> Self {
+        Liveness::of_ir(func.ir())
+    }
+}
@@ -0,0 +1,42 @@
+use std::collections::HashSet;
+
+use crate::{
+    ir::{Function, IrOperand, NodeRef},
+    opt::{FunctionAnalysis, reachable, walk_nodes},
+};
+
+/// The nodes which are (transitively) needed by a node with side effects
+/// (like returns and calls) which is reachable
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LiveNodes {
+    live: HashSet<usize>,
+}
+
+impl LiveNodes {
+    /// Marks the nodes of the ir which are needed by its side effects
+    pub fn of_ir(ir: &[IrOperand]) -> Self {
+        let roots: Vec<IrOperand> = reachable(ir)
+            .iter()
+            .filter(|op| {
+                op.node()
+                    .is_some_and(|node| node.borrow().has_side_effects())
+            })
+            .cloned()
+            .collect();
+
+        Self {
+            live: walk_nodes(&roots).iter().map(|node| node.id()).collect(),
+        }
+    }
+
+    /// Returns if the node is needed
+    pub fn contains(&self, node: &NodeRef) -> bool {
+        self.live.contains(&node.id())
+    }
+}
+
+impl FunctionAnalysis for LiveNodes {
+    fn compute(func: &Function) -> Self {
+        LiveNodes::of_ir(func.ir())
+    }
+}
@@ -0,0 +1,195 @@
+//! Cached analyses of functions and modules
+//!
+//! Analyses are computed on request by the `AnalysisManager` and cached until a
+//! pass changes the ir without preserving them
+
+use std::{
+    any::{Any, TypeId},
+    collections::{HashMap, HashSet},
+};
+
+pub use crate::ir::walk_nodes;
+use crate::ir::{Function, IrOperand, Module};
+
+/// The call graph of a module
+pub mod callgraph;
+/// Dominator tree
+pub mod dominators;
+/// Live ranges of values
+pub mod liveness;
+/// Nodes needed by side effects
+pub mod livenodes;
+/// Use counts of values
+pub mod uses;
+
+pub use callgraph::*;
+pub use dominators::*;
+pub use liveness::*;
+pub use livenodes::*;
+pub use uses::*;
+
+/// An analysis which is computed for a single function
+pub trait FunctionAnalysis: Any + Send + Sync + Sized {
+    /// Computes the analysis for the function
+    fn compute(func: &Function) -> Self;
+}
+
+/// An analysis which is computed for an entire module
+pub trait ModuleAnalysis: Any + Send + Sync + Sized {
+    /// Computes the analysis for the module
+    fn compute(module: &Module) -> Self;
+}
+
+/// The analyses which are still valid after a pass changed the ir
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PreservedAnalyses {
+    all: bool,
+    preserved: HashSet<TypeId>,
+}
+
+impl PreservedAnalyses {
+    /// No analysis is preserved
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// All analyses are preserved
+    pub fn all() -> Self {
+        Self {
+            all: true,
+            preserved: HashSet::new(),
+        }
+    }
+
+    /// Marks the analysis as preserved
+    pub fn preserve<A: Any>(mut self) -> Self {
+        self.preserved.insert(TypeId::of::<A>());
+        self
+    }
+
+    /// Returns if the analysis is preserved
+    pub fn is_preserved<A: Any>(&self) -> bool {
+        self.is_preserved_id(TypeId::of::<A>())
+    }
+
+    fn is_preserved_id(&self, id: TypeId) -> bool {
+        self.all || self.preserved.contains(&id)
+    }
+}
+
+type Cache = HashMap<TypeId, Box<dyn Any + Send + Sync>>;
+
+/// Computes analyses on request and caches them
+///
+/// Example:
+/// ```rust
+/// use jacob::ir::{Function, TypeMetadata};
+/// use jacob::opt::{AnalysisManager, PreservedAnalyses, UseCounts};
+///
+/// let mut func = Function::new("double");
+/// let x = func.add_arg(TypeMetadata::Int64);
+/// let out = func.add(&x, &x);
+/// func.ret(&out);
+///
+/// let mut analyses = AnalysisManager::new();
+/// assert_eq!(analyses.get::<UseCounts>(&func).arg_uses(0), 2);
+/// assert_eq!(analyses.get::<UseCounts>(&func).uses(out.node().unwrap()), 1);
+/// assert_eq!(analyses.computed(), 1);
+///
+/// analyses.invalidate(&func, &PreservedAnalyses::none());
+/// analyses.get::<UseCounts>(&func);
+/// assert_eq!(analyses.computed(), 2);
+/// ```
+#[derive(Default)]
+pub struct AnalysisManager {
+    funcs: HashMap<String, Cache>,
+    module: Cache,
+    computed: usize,
+}
+
+impl AnalysisManager {
+    /// Creates a new analysis manager with an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the analysis of the function (computing it if it isn't cached)
+    pub fn get<A: FunctionAnalysis>(&mut self, func: &Function) -> &A {
+        let id = TypeId::of::<A>();
+
+        if !self
+            .funcs
+            .get(&func.name)
+            .is_some_and(|x| x.contains_key(&id))
+        {
+            self.computed += 1;
+            self.funcs
+                .entry(func.name.to_owned())
+                .or_default()
+                .insert(id, Box::new(A::compute(func)));
+        }
+
+        self.funcs[&func.name][&id]
+            .downcast_ref()
+            .expect("the cache is keyed by the analysis type")
+    }
+
+    /// Returns the analysis of the module (computing it if it isn't cached)
+    pub fn get_module<A: ModuleAnalysis>(&mut self, module: &Module) -> &A {
+        let id = TypeId::of::<A>();
+
+        if !self.module.contains_key(&id) {
+            self.computed += 1;
+            self.module.insert(id, Box::new(A::compute(module)));
+        }
+
+        self.module[&id]
+            .downcast_ref()
+            .expect("the cache is keyed by the analysis type")
+    }
+
+    /// Returns the analysis of the function if it is cached
+    pub fn cached<A: FunctionAnalysis>(&self, func: &Function) -> Option<&A> {
+        self.funcs
+            .get(&func.name)?
+            .get(&TypeId::of::<A>())?
+            .downcast_ref()
+    }
+
+    /// Drops the analyses of the changed function which aren't preserved
+    pub fn invalidate(&mut self, func: &Function, preserved: &PreservedAnalyses) {
+        if let Some(cache) = self.funcs.get_mut(&func.name) {
+            cache.retain(|id, _| preserved.is_preserved_id(*id));
+        }
+    }
+
+    /// Drops the analyses of the module which aren't preserved
+    pub fn invalidate_module(&mut self, preserved: &PreservedAnalyses) {
+        self.module.retain(|id, _| preserved.is_preserved_id(*id));
+    }
+
+    /// Drops all cached analyses
+    pub fn clear(&mut self) {
+        self.funcs.clear();
+        self.module.clear();
+    }
+
+    /// Returns how many analyses were computed (and not taken from the cache)
+    pub fn computed(&self) -> usize {
+        self.computed
+    }
+}
+
+/// Returns the part of the ir which is executed (the code after a call which never
+/// returns is unreachable)
+pub(crate) fn reachable(ir: &[IrOperand]) -> &[IrOperand] {
+    let end = ir
+        .iter()
+        .position(|op| {
+            op.node()
+                .is_some_and(|node| node.borrow().is_noreturn_call())
+        })
+        .map_or(ir.len(), |pos| pos + 1);
+
+    &ir[..end]
+}
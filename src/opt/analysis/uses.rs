@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+
+use crate::{
+    ir::{Function, IrOperand, NodeRef},
+    opt::{FunctionAnalysis, walk_nodes},
+};
+
+/// Counts how often the outputs of nodes and the arguments are used
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct UseCounts {
+    nodes: HashMap<usize, usize>,
+    args: HashMap<usize, usize>,
+}
+
+impl UseCounts {
+    /// Counts the uses in the ir
+    pub fn of_ir(ir: &[IrOperand]) -> Self {
+        let mut counts = UseCounts::default();
+
+        for node in walk_nodes(ir) {
+            for op in node.borrow().ops() {
+                match op.strip_drop() {
+                    IrOperand::Out(used) => *counts.nodes.entry(used.id()).or_insert(0) += 1,
+                    IrOperand::Arg { num, .. } => *counts.args.entry(*num).or_insert(0) += 1,
+                    _ => {}
+                }
+            }
+        }
+
+        counts
+    }
+
+    /// Returns how often the output of the node is used
+    pub fn uses(&self, node: &NodeRef) -> usize {
+        self.nodes.get(&node.id()).copied().unwrap_or(0)
+    }
+
+    /// Removes one use of the node and returns how many uses are left
+    pub(crate) fn decrement(&mut self, node: &NodeRef) -> usize {
+        let count = self.nodes.entry(node.id()).or_insert(0);
+        *count = count.saturating_sub(1);
+        *count
+    }
+
+    /// Returns how often the argument is used
+    pub fn arg_uses(&self, num: usize) -> usize {
+        self.args.get(&num).copied().unwrap_or(0)
+    }
+}
+
+impl FunctionAnalysis for UseCounts {
+    fn compute(func: &Function) -> Self {
+        UseCounts::of_ir(func.ir())
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{
+    interp::Value,
+    ir::{Function, IrNode, IrOpcode, IrOperand},
+    opt::{AnalysisManager, CallGraph, Dominators, Optimization, PreservedAnalyses, walk_nodes},
+};
+
+/// Constant folding and propagation
+///
+/// Nodes whose operands are all constant numbers are evaluated (wrapping around
+/// like the type of the node does) and their uses are replaced by the result
+///
+/// Example:
+/// ```rust
+/// use jacob::ir::{Function, IrOperand, Module, TypeMetadata};
+/// use jacob::opt::{ConstFold, Pipeline};
+///
+/// let int = |num| IrOperand::ConstNum { num, ty: TypeMetadata::Int64 };
+///
+/// let mut func = Function::new("minus_one");
+/// func.set_ret(TypeMetadata::Int64);
+/// let two = func.add(&int(1), &int(1));
+/// let out = func.sub(&two, &int(3));
+/// func.ret(&out);
+///
+/// let mut module = Module::new();
+/// module.add_func(func);
+/// assert!(module.run_pipeline(&Pipeline::new().with_pass(ConstFold {})));
+///
+/// let ret = module.funcs[0].ir().last().unwrap().node().unwrap().borrow();
+/// assert_eq!(ret.ops()[0], int(u64::MAX as usize));
+/// ```
+pub struct ConstFold {}
+
+impl ConstFold {
+    /// Evaluates the node if all of its operands are constant numbers
+    pub fn fold(node: &IrNode) -> Option<IrOperand> {
+        let ty = node.get_ty()?;
+
+        let ops = node
+            .ops()
+            .iter()
+            .map(|op| match op.strip_drop() {
+                IrOperand::ConstNum { num, ty } => Some(Value::new(*ty, *num as u64)),
+                _ => None,
+            })
+            .collect::<Option<Vec<Value>>>()?;
+
+        if ops.iter().any(|op| op.ty() != ty) {
+            return None;
+        }
+
+        let out = match (node.opcode(), ops.as_slice()) {
+            (IrOpcode::Add, [lhs, rhs]) => lhs.wrapping_add(rhs),
+            (IrOpcode::Sub, [lhs, rhs]) => lhs.wrapping_sub(rhs),
+            _ => return None,
+        };
+
+        Some(IrOperand::ConstNum {
+            num: out.bits() as usize,
+            ty,
+        })
+    }
+}
+
+impl Optimization for ConstFold {
+    fn name(&self) -> &'static str {
+        "Constant Folding"
+    }
+
+    fn preserved(&self) -> PreservedAnalyses {
+        // folded values become constants, so no nodes are created and the remaining
+        // ones keep their order (calls are never folded)
+        PreservedAnalyses::none()
+            .preserve::<Dominators>()
+            .preserve::<CallGraph>()
+    }
+
+    fn run(&self, func: &mut Function, _analyses: &mut AnalysisManager) -> bool {
+        let mut changed = false;
+
+        // operands come before their users, so folded results propagate in one walk
+        for node in walk_nodes(&func.ir) {
+            let mut node = node.borrow_mut();
+
+            for op in node.ops.iter_mut() {
+                let Some(folded) = op.node().and_then(|used| ConstFold::fold(&used.borrow()))
+                else {
+                    continue;
+                };
+
+                *op = folded;
+                changed = true;
+            }
+        }
+
+        // the folded nodes aren't used anymore
+        let len = func.ir.len();
+        func.ir.retain(|op| {
+            op.node()
+                .is_none_or(|node| ConstFold::fold(&node.borrow()).is_none())
+        });
+
+        changed || func.ir.len() != len
+    }
+}
@@ -0,0 +1,72 @@
+use crate::{
+    ir::{Function, IrOpcode, IrOperand},
+    opt::{AnalysisManager, CallGraph, Dominators, Optimization, PreservedAnalyses, walk_nodes},
+};
+
+/// Copy propagation
+///
+/// Uses of the output of a `Copy` node are replaced by the copied operand
+///
+/// Example:
+/// ```rust
+/// use jacob::ir::{Function, Module, TypeMetadata};
+/// use jacob::opt::{CopyProp, Pipeline};
+///
+/// let mut func = Function::new("id");
+/// func.set_ret(TypeMetadata::Int64);
+/// let x = func.add_arg(TypeMetadata::Int64);
+/// let a = func.copy(&x);
+/// let b = func.copy(&a);
+/// func.ret(&b);
+///
+/// let mut module = Module::new();
+/// module.add_func(func);
+/// assert!(module.run_pipeline(&Pipeline::new().with_pass(CopyProp {})));
+///
+/// let ir = module.funcs[0].ir();
+/// assert_eq!(ir.len(), 1);
+/// assert_eq!(ir[0].node().unwrap().borrow().ops(), &[x]);
+/// ```
+pub struct CopyProp {}
+
+impl CopyProp {
+    /// Returns the copied operand if the operand is the output of a copy
+    fn source(op: &IrOperand) -> Option<IrOperand> {
+        let node = op.node()?.borrow();
+
+        (node.opcode() == IrOpcode::Copy).then(|| node.ops()[0].strip_drop().clone())
+    }
+}
+
+impl Optimization for CopyProp {
+    fn name(&self) -> &'static str {
+        "Copy Propagation"
+    }
+
+    fn preserved(&self) -> PreservedAnalyses {
+        // users of a copy read its operand instead, which is defined before the copy,
+        // so no nodes are created and calls aren't touched
+        PreservedAnalyses::none()
+            .preserve::<Dominators>()
+            .preserve::<CallGraph>()
+    }
+
+    fn run(&self, func: &mut Function, _analyses: &mut AnalysisManager) -> bool {
+        let mut changed = false;
+
+        // the operands of copies are forwarded first, so chains of copies collapse
+        for node in walk_nodes(&func.ir) {
+            for op in node.borrow_mut().ops.iter_mut() {
+                if let Some(source) = CopyProp::source(op) {
+                    *op = source;
+                    changed = true;
+                }
+            }
+        }
+
+        let len = func.ir.len();
+        func.ir.retain(|op| CopyProp::source(op).is_none());
+
+        changed || func.ir.len() != len
+    }
+}
@@ -1,8 +1,91 @@
-use std::collections::{HashMap, HashSet};
-
-use crate::{ir::Function, opt::Optimization};
+use crate::{
+    ir::Function,
+    opt::{AnalysisManager, CallGraph, Dominators, LiveNodes, Optimization, PreservedAnalyses},
+};
 
 /// Dead code elimination
+///
+/// Marks all nodes which are (transitively) used by a node with side effects
+/// (like returns and calls) and removes all other nodes
+///
+/// Example:
+/// ```rust
+/// use jacob::interp::{Interpreter, Value};
+/// use jacob::ir::{Function, Module, TypeMetadata};
+/// use jacob::opt::{Dce, Pipeline};
+///
+/// let mut func = Function::new("sum");
+/// func.set_ret(TypeMetadata::Int64);
+/// let x = func.add_arg(TypeMetadata::Int64);
+/// let y = func.add_arg(TypeMetadata::Int64);
+///
+/// let unused = func.sub(&x, &y);
+/// func.add(&unused, &x);
+///
+/// let sum = func.add(&x, &y);
+/// let double = func.add(&sum, &sum);
+/// let out = func.add(&double, &sum);
+/// func.ret(&out);
+///
+/// let mut module = Module::new();
+/// module.add_func(func);
+/// assert!(module.run_pipeline(&Pipeline::new().with_pass(Dce {})));
+///
+/// // the live nodes are kept in their order, only the dead ones are gone
+/// let func = &module.funcs[0];
+/// assert_eq!(func.ir().len(), 4);
+/// assert!(func.ir()[0].node().unwrap().ptr_eq(sum.node().unwrap()));
+///
+/// let out = Interpreter::new().run(func, &[Value::int64(2), Value::int64(3)]).unwrap();
+/// assert_eq!(out, Some(Value::int64(15)));
+///
+/// assert!(!module.run_pipeline(&Pipeline::new().with_pass(Dce {})));
+/// ```
+///
+/// Calls are kept even if their output isn't used:
+/// ```rust
+/// use jacob::ir::{Function, Module, TypeMetadata};
+/// use jacob::opt::{Dce, Pipeline};
+///
+/// let mut func = Function::new("main");
+/// let x = func.add_arg(TypeMetadata::Int64);
+/// let arg = func.add(&x, &x);
+/// func.call("log", &[arg], Some(TypeMetadata::Int64));
+/// func.add(&x, &x);
+/// func.ret(&x);
+///
+/// let mut module = Module::new();
+/// module.add_func(func);
+/// module.run_pipeline(&Pipeline::new().with_pass(Dce {}));
+///
+/// let ir = module.funcs[0].ir();
+/// assert_eq!(ir.len(), 3);
+/// assert!(ir[1].node().unwrap().borrow().is_call());
+/// assert!(ir[2].node().unwrap().borrow().is_ret());
+/// ```
+///
+/// Unless the callee doesn't read or write memory:
+/// ```rust
+/// use jacob::ir::{Function, FunctionAttribute, Module, TypeMetadata};
+/// use jacob::opt::{Dce, Pipeline};
+///
+/// let mut square = Function::new("square");
+/// square.set_ret(TypeMetadata::Int64);
+/// let x = square.add_arg(TypeMetadata::Int64);
+/// square.ret(&x);
+/// square.add_attr(FunctionAttribute::ReadNone);
+///
+/// let mut func = Function::new("main");
+/// let x = func.add_arg(TypeMetadata::Int64);
+/// func.try_call(&square, &[x.clone()]).unwrap();
+/// func.ret(&x);
+///
+/// let mut module = Module::new();
+/// module.add_func(func);
+/// module.run_pipeline(&Pipeline::new().with_pass(Dce {}));
+///
+/// assert_eq!(module.funcs[0].ir().len(), 1);
+/// ```
 pub struct Dce {}
 
 impl Optimization for Dce {
@@ -10,35 +93,20 @@ impl Optimization for Dce {
         "Dead Code Elimination"
     }
 
-    fn run(&self, func: &mut Function) {
-        let mut live: HashSet<u64> = HashSet::new();
-        let mut users: HashMap<u64, u64> = HashMap::new();
-
-        for op in func.ir.iter().rev() {
-            let node = op.force_node().borrow();
-            if node.is_ret() {
-                live.insert(node.hash_u64());
-            }
+    fn run(&self, func: &mut Function, analyses: &mut AnalysisManager) -> bool {
+        let live = analyses.get::<LiveNodes>(func);
 
-            for user in node.ops.iter() {
-                if user.is_out() {
-                    *users
-                        .entry(user.force_node().borrow().hash_u64())
-                        .or_insert(0) += 1;
-                }
-            }
-        }
+        let len = func.ir.len();
+        func.ir
+            .retain(|op| op.node().is_some_and(|node| live.contains(node)));
 
-        let mut to_delete = Vec::new();
-        for (id, users) in users {
-            if users <= 1 {
-                to_delete.push(id);
-            }
-        }
+        func.ir.len() != len
+    }
 
-        func.ir.retain(|op| {
-            live.contains(&op.force_node().borrow().hash_u64())
-                && !to_delete.contains(&op.force_node().borrow().hash_u64())
-        });
+    fn preserved(&self) -> PreservedAnalyses {
+        // only nodes which aren't needed (and therefore no calls) are removed
+        PreservedAnalyses::none()
+            .preserve::<Dominators>()
+            .preserve::<CallGraph>()
     }
 }
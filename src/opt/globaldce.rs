@@ -0,0 +1,78 @@
+use crate::{
+    ir::{Function, Module},
+    opt::{AnalysisManager, CallGraph, Optimization, PreservedAnalyses},
+};
+
+/// Dead function elimination
+///
+/// Starts at the functions which are visible outside of the module, follows
+/// their calls and removes all `Internal` functions which are never reached
+/// (modules don't have globals yet, constants are only emitted when they are used)
+///
+/// Example:
+/// ```rust
+/// use jacob::ir::{Function, Module, TypeMetadata};
+/// use jacob::opt::{GlobalDce, Pipeline};
+///
+/// let mut helper = Function::new("helper");
+/// let x = helper.add_arg(TypeMetadata::Int64);
+/// helper.set_ret(TypeMetadata::Int64);
+/// helper.ret(&x);
+/// helper.internal();
+///
+/// let mut unused = Function::new("unused");
+/// let x = unused.add_arg(TypeMetadata::Int64);
+/// let out = unused.call("helper", &[x], Some(TypeMetadata::Int64));
+/// unused.ret(&out);
+/// unused.internal();
+///
+/// let mut main = Function::new("main");
+/// let x = main.add_arg(TypeMetadata::Int64);
+/// main.set_ret(TypeMetadata::Int64);
+/// let out = main.call("helper", &[x], Some(TypeMetadata::Int64));
+/// main.ret(&out);
+///
+/// let mut module = Module::new();
+/// module.add_func(helper);
+/// module.add_func(unused);
+/// module.add_func(main);
+///
+/// assert!(module.run_pipeline(&Pipeline::new().with_pass(GlobalDce {})));
+///
+/// let names: Vec<&str> = module.funcs.iter().map(|func| func.name()).collect();
+/// assert_eq!(names, vec!["helper", "main"]);
+/// ```
+pub struct GlobalDce {}
+
+impl Optimization for GlobalDce {
+    fn name(&self) -> &'static str {
+        "Global Dead Code Elimination"
+    }
+
+    fn run(&self, _func: &mut Function, _analyses: &mut AnalysisManager) -> bool {
+        false
+    }
+
+    fn run_module(&self, module: &mut Module, analyses: &mut AnalysisManager) -> bool {
+        let roots = module
+            .funcs
+            .iter()
+            .filter(|func| func.visibility().is_exported())
+            .map(|func| func.name.as_str());
+
+        let live = analyses.get_module::<CallGraph>(module).reachable(roots);
+
+        let len = module.funcs.len();
+        module.funcs.retain(|func| {
+            let keep = live.contains(&func.name);
+
+            if !keep {
+                analyses.invalidate(func, &PreservedAnalyses::none());
+            }
+
+            keep
+        });
+
+        module.funcs.len() != len
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use crate::{
+    ir::{Function, IrNode, IrOpcode, IrOperand, NodeRef, TypeMetadata},
+    opt::{AnalysisManager, Dominators, Optimization, walk_nodes},
+};
+
+/// The value number of an operand
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ValueKey {
+    Arg(usize, TypeMetadata),
+    Const(usize, TypeMetadata),
+    /// The id of the node which computes the value
+    Node(usize),
+}
+
+/// The structure of a pure node with the value numbers of its operands
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Expr {
+    opcode: IrOpcode,
+    ty: Option<TypeMetadata>,
+    ops: Vec<ValueKey>,
+}
+
+/// Global value numbering
+///
+/// Pure nodes which compute the same value (the same opcode on the same operands)
+/// are merged into the first of them
+///
+/// Example:
+/// ```rust
+/// use jacob::ir::{Function, Module, TypeMetadata};
+/// use jacob::opt::{Gvn, Pipeline};
+///
+/// let mut func = Function::new("double_sum");
+/// func.set_ret(TypeMetadata::Int64);
+/// let x = func.add_arg(TypeMetadata::Int64);
+/// let y = func.add_arg(TypeMetadata::Int64);
+/// let a = func.add(&x, &y);
+/// let b = func.add(&y, &x);
+/// let out = func.add(&a, &b);
+/// func.ret(&out);
+///
+/// let mut module = Module::new();
+/// module.add_func(func);
+/// assert!(module.run_pipeline(&Pipeline::new().with_pass(Gvn {})));
+///
+/// let ir = module.funcs[0].ir();
+/// assert_eq!(ir.len(), 3);
+///
+/// let sum = ir[1].node().unwrap().borrow();
+/// assert!(sum.ops()[0].node().unwrap().ptr_eq(sum.ops()[1].node().unwrap()));
+/// ```
+pub struct Gvn {}
+
+impl Gvn {
+    /// Returns if the node can be merged with other nodes computing the same value
+    fn is_pure(node: &IrNode) -> bool {
+        node.has_out()
+            && matches!(
+                node.opcode(),
+                IrOpcode::Add | IrOpcode::Sub | IrOpcode::Copy
+            )
+    }
+
+    /// Returns the value number of the operand
+    fn key(op: &IrOperand) -> ValueKey {
+        match op.strip_drop() {
+            IrOperand::Arg { num, ty } => ValueKey::Arg(*num, *ty),
+            IrOperand::ConstNum { num, ty } => ValueKey::Const(*num, *ty),
+            IrOperand::Out(node) => ValueKey::Node(node.id()),
+            IrOperand::Drop(_) => unreachable!("drops are stripped"),
+        }
+    }
+
+    /// Returns the structure of the node (and the one with swapped operands for
+    /// commutative nodes)
+    fn exprs(node: &IrNode) -> Vec<Expr> {
+        let expr = Expr {
+            opcode: node.opcode(),
+            ty: node.get_ty(),
+            ops: node.ops().iter().map(Gvn::key).collect(),
+        };
+
+        let mut exprs = vec![expr.clone()];
+
+        if node.opcode() == IrOpcode::Add {
+            let mut swapped = expr;
+            swapped.ops.reverse();
+            exprs.push(swapped);
+        }
+
+        exprs
+    }
+}
+
+impl Optimization for Gvn {
+    fn name(&self) -> &'static str {
+        "Global Value Numbering"
+    }
+
+    fn run(&self, func: &mut Function, analyses: &mut AnalysisManager) -> bool {
+        let doms = analyses.get::<Dominators>(func);
+
+        let mut leaders: HashMap<Expr, NodeRef> = HashMap::new();
+        let mut replaced: HashMap<usize, NodeRef> = HashMap::new();
+        let mut changed = false;
+
+        for node in walk_nodes(&func.ir) {
+            for op in node.borrow_mut().ops.iter_mut() {
+                if let IrOperand::Out(used) = op
+                    && let Some(leader) = replaced.get(&used.id())
+                {
+                    *op = IrOperand::Out(leader.clone());
+                    changed = true;
+                }
+            }
+
+            if !Gvn::is_pure(&node.borrow()) {
+                continue;
+            }
+
+            let exprs = Gvn::exprs(&node.borrow());
+
+            // the leader has to be computed on every path to the node
+            let leader = exprs
+                .iter()
+                .filter_map(|expr| leaders.get(expr))
+                .find(|leader| doms.dominates(leader, &node));
+
+            match leader {
+                Some(leader) => {
+                    replaced.insert(node.id(), leader.clone());
+                }
+                None => {
+                    let [expr, ..] = exprs.as_slice() else {
+                        unreachable!("there is always at least one expression");
+                    };
+                    leaders.insert(expr.clone(), node.clone());
+                }
+            }
+        }
+
+        let len = func.ir.len();
+        func.ir.retain(|op| {
+            op.node()
+                .is_none_or(|node| !replaced.contains_key(&node.id()))
+        });
+
+        changed || func.ir.len() != len
+    }
+}
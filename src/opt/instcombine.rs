@@ -0,0 +1,414 @@
+use std::{cell::RefCell, collections::HashMap, sync::Arc};
+
+use crate::{
+    interp::Value,
+    ir::{
+        Function, IrNode, IrOperand, Matcher, TypeMetadata, m_add, m_any, m_c_add, m_capture,
+        m_const, m_const_any, m_sub,
+    },
+    opt::{AnalysisManager, CallGraph, Optimization, PreservedAnalyses, walk_nodes},
+};
+
+/// A rewrite rule of the instruction combiner
+pub trait CombineRule: Send + Sync {
+    /// Returns the name of the rule
+    fn name(&self) -> &'static str;
+
+    /// Returns a simpler operand which computes the same value (if the rule applies)
+    fn apply(&self, op: &IrOperand) -> Option<IrOperand>;
+}
+
+/// Returns the constant number of the type
+fn int(num: u64, ty: TypeMetadata) -> IrOperand {
+    IrOperand::ConstNum {
+        num: Value::new(ty, num).bits() as usize,
+        ty,
+    }
+}
+
+/// Returns the addition which replaces `op` (it keeps the source location of `op`)
+fn add(op: &IrOperand, lhs: &IrOperand, rhs: &IrOperand) -> IrOperand {
+    let out = IrNode::add(lhs, rhs);
+
+    if let (Some(old), Some(new)) = (op.node(), out.node()) {
+        new.borrow_mut().loc = old.borrow().loc.clone();
+    }
+
+    out
+}
+
+/// Returns if both operands always have the same value
+///
+/// Outputs of different nodes only do if the nodes don't have side effects and
+/// compute the same opcode on the same values
+fn same_value(lhs: &IrOperand, rhs: &IrOperand) -> bool {
+    match (lhs.strip_drop(), rhs.strip_drop()) {
+        (IrOperand::Out(lhs), IrOperand::Out(rhs)) => {
+            if lhs.ptr_eq(rhs) {
+                return true;
+            }
+
+            let (lhs, rhs) = (lhs.borrow(), rhs.borrow());
+
+            !lhs.has_side_effects()
+                && lhs.opcode == rhs.opcode
+                && lhs.ty == rhs.ty
+                && lhs.ops.len() == rhs.ops.len()
+                && lhs.ops.iter().zip(&rhs.ops).all(|(l, r)| same_value(l, r))
+        }
+        (IrOperand::Out(_), _) | (_, IrOperand::Out(_)) => false,
+        (lhs, rhs) => lhs == rhs,
+    }
+}
+
+/// Returns the captured operand and the number of the captured constant
+fn captured(op: RefCell<Option<IrOperand>>, c: RefCell<Option<IrOperand>>) -> (IrOperand, u64) {
+    let op = op.into_inner().expect("the operand is captured");
+    let c = c
+        .into_inner()
+        .and_then(|c| c.const_num())
+        .expect("the constant is captured");
+
+    (op, c as u64)
+}
+
+/// `c + x` -> `x + c`
+pub struct ConstToRhs;
+
+impl CombineRule for ConstToRhs {
+    fn name(&self) -> &'static str {
+        "const to rhs"
+    }
+
+    fn apply(&self, op: &IrOperand) -> Option<IrOperand> {
+        let (x, c) = (RefCell::new(None), RefCell::new(None));
+
+        if !m_add(m_capture(&c, m_const_any()), m_capture(&x, m_any())).matches(op)
+            || x.borrow().as_ref().is_some_and(|x| x.const_num().is_some())
+        {
+            return None;
+        }
+
+        let (x, c) = (x.into_inner()?, c.into_inner()?);
+        Some(add(op, &x, &c))
+    }
+}
+
+/// `x + 0` -> `x`
+pub struct AddZero;
+
+impl CombineRule for AddZero {
+    fn name(&self) -> &'static str {
+        "add zero"
+    }
+
+    fn apply(&self, op: &IrOperand) -> Option<IrOperand> {
+        let x = RefCell::new(None);
+
+        m_c_add(m_capture(&x, m_any()), m_const(0))
+            .matches(op)
+            .then(|| x.into_inner())?
+    }
+}
+
+/// `x - 0` -> `x`
+pub struct SubZero;
+
+impl CombineRule for SubZero {
+    fn name(&self) -> &'static str {
+        "sub zero"
+    }
+
+    fn apply(&self, op: &IrOperand) -> Option<IrOperand> {
+        let x = RefCell::new(None);
+
+        m_sub(m_capture(&x, m_any()), m_const(0))
+            .matches(op)
+            .then(|| x.into_inner())?
+    }
+}
+
+/// `x - x` -> `0`
+///
+/// Example:
+/// ```rust
+/// use jacob::ir::{Function, Module, TypeMetadata};
+/// use jacob::opt::{InstCombine, Pipeline};
+///
+/// let mut func = Function::new("noise");
+/// func.set_ret(TypeMetadata::Int64);
+/// let x = func.add_arg(TypeMetadata::Int64);
+/// let a = func.call("rand", &[x.clone()], Some(TypeMetadata::Int64));
+/// let b = func.call("rand", &[x], Some(TypeMetadata::Int64));
+/// let out = func.sub(&a, &b);
+/// func.ret(&out);
+///
+/// // both calls can return different values
+/// let mut module = Module::new();
+/// module.add_func(func);
+/// assert!(!module.run_pipeline(&Pipeline::new().with_pass(InstCombine::new())));
+/// ```
+pub struct SubSelf;
+
+impl CombineRule for SubSelf {
+    fn name(&self) -> &'static str {
+        "sub self"
+    }
+
+    fn apply(&self, op: &IrOperand) -> Option<IrOperand> {
+        let (lhs, rhs) = (RefCell::new(None), RefCell::new(None));
+
+        if !m_sub(m_capture(&lhs, m_any()), m_capture(&rhs, m_any())).matches(op)
+            || !same_value(lhs.borrow().as_ref()?, rhs.borrow().as_ref()?)
+        {
+            return None;
+        }
+
+        Some(int(0, op.get_ty()))
+    }
+}
+
+/// `(x + c1) + c2` -> `x + (c1 + c2)`
+pub struct AddConsts;
+
+impl CombineRule for AddConsts {
+    fn name(&self) -> &'static str {
+        "add consts"
+    }
+
+    fn apply(&self, op: &IrOperand) -> Option<IrOperand> {
+        let (x, c1, c2) = (RefCell::new(None), RefCell::new(None), RefCell::new(None));
+
+        let inner = m_c_add(m_capture(&x, m_any()), m_capture(&c1, m_const_any()));
+        if !m_c_add(inner, m_capture(&c2, m_const_any())).matches(op) {
+            return None;
+        }
+
+        let c2 = c2.into_inner()?.const_num()? as u64;
+        let (x, c1) = captured(x, c1);
+
+        Some(add(op, &x, &int(c1.wrapping_add(c2), op.get_ty())))
+    }
+}
+
+/// `x - (-c)` -> `x + c`
+pub struct SubNegConst;
+
+impl CombineRule for SubNegConst {
+    fn name(&self) -> &'static str {
+        "sub negative const"
+    }
+
+    fn apply(&self, op: &IrOperand) -> Option<IrOperand> {
+        let (x, c) = (RefCell::new(None), RefCell::new(None));
+
+        if !m_sub(m_capture(&x, m_any()), m_capture(&c, m_const_any())).matches(op) {
+            return None;
+        }
+
+        let ty = op.get_ty();
+        let (x, c) = captured(x, c);
+
+        let c = Value::new(ty, c);
+        if !ty.signed() || c.as_i64() >= 0 {
+            return None;
+        }
+
+        Some(add(op, &x, &int(c.as_i64().wrapping_neg() as u64, ty)))
+    }
+}
+
+/// Instruction combining
+///
+/// Rewrites nodes into simpler ones using a set of algebraic identities. It also
+/// canonicalizes the ir (for example constants are always the rhs of additions),
+/// so the backends need to handle fewer shapes
+///
+/// Example:
+/// ```rust
+/// use jacob::ir::{Function, IrOperand, Module, TypeMetadata};
+/// use jacob::opt::{InstCombine, Pipeline};
+///
+/// let int = |num| IrOperand::ConstNum { num, ty: TypeMetadata::Int64 };
+///
+/// let mut func = Function::new("add_three");
+/// func.set_loc("add.src", 1, 1);
+/// func.set_ret(TypeMetadata::Int64);
+/// let x = func.add_arg(TypeMetadata::Int64);
+/// let one = func.add(&int(1), &x);
+/// let three = func.add(&one, &int(2));
+/// let out = func.sub(&three, &int(0));
+/// func.ret(&out);
+///
+/// let mut module = Module::new();
+/// module.add_func(func);
+/// assert!(module.run_pipeline(&Pipeline::new().with_pass(InstCombine::new())));
+///
+/// let ret = module.funcs[0].ir().last().unwrap().node().unwrap().borrow();
+/// let add = ret.ops()[0].node().unwrap().borrow();
+/// assert_eq!(add.ops(), &[x, int(3)]);
+/// // the new node keeps the source location of the one it replaces
+/// assert_eq!(add.loc().unwrap().line, 1);
+/// ```
+///
+/// All users of a rewritten value share the same replacement:
+/// ```rust
+/// use jacob::ir::{Function, IrOperand, Module, TypeMetadata};
+/// use jacob::opt::{InstCombine, Pipeline};
+///
+/// let one = IrOperand::ConstNum { num: 1, ty: TypeMetadata::Int64 };
+///
+/// let mut func = Function::new("triple");
+/// func.set_ret(TypeMetadata::Int64);
+/// let x = func.add_arg(TypeMetadata::Int64);
+/// let a = func.add(&one, &x);
+/// let b = func.add(&a, &a);
+/// let c = func.add(&b, &a);
+/// func.ret(&c);
+///
+/// let mut module = Module::new();
+/// module.add_func(func);
+/// module.run_pipeline(&Pipeline::new().with_pass(InstCombine::new()));
+///
+/// let (b, c) = (b.node().unwrap().borrow(), c.node().unwrap().borrow());
+/// let a = b.ops()[0].node().unwrap();
+/// assert!(b.ops()[1].node().unwrap().ptr_eq(a));
+/// assert!(c.ops()[1].node().unwrap().ptr_eq(a));
+/// ```
+///
+/// The created nodes can be merged by a later global value numbering:
+/// ```rust
+/// use jacob::ir::{Function, IrOperand, Module, TypeMetadata};
+/// use jacob::opt::{Gvn, InstCombine, Pipeline};
+///
+/// let int = |num| IrOperand::ConstNum { num, ty: TypeMetadata::Int64 };
+///
+/// let mut func = Function::new("twice");
+/// func.set_ret(TypeMetadata::Int64);
+/// let x = func.add_arg(TypeMetadata::Int64);
+/// let one = func.add(&x, &int(1));
+/// let a = func.add(&one, &int(2));
+/// let b = func.add(&x, &int(3));
+/// let out = func.add(&a, &b);
+/// func.ret(&out);
+///
+/// let mut module = Module::new();
+/// module.add_func(func);
+/// module.run_pipeline(
+///     &Pipeline::new()
+///         .with_pass(Gvn {})
+///         .with_pass(InstCombine::new())
+///         .with_pass(Gvn {}),
+/// );
+///
+/// let out = out.node().unwrap().borrow();
+/// assert!(out.ops()[0].node().unwrap().ptr_eq(out.ops()[1].node().unwrap()));
+/// ```
+pub struct InstCombine {
+    rules: Vec<Arc<dyn CombineRule>>,
+}
+
+impl Default for InstCombine {
+    fn default() -> Self {
+        InstCombine::new()
+    }
+}
+
+impl InstCombine {
+    /// The maximal number of rewrites of a single operand
+    const MAX_REWRITES: usize = 32;
+
+    /// Creates an instruction combiner with the default rules
+    pub fn new() -> Self {
+        // ToDo: multiplications by a power of two into shifts once the ir has those opcodes
+        InstCombine::empty()
+            .with_rule(ConstToRhs)
+            .with_rule(AddZero)
+            .with_rule(SubZero)
+            .with_rule(SubSelf)
+            .with_rule(AddConsts)
+            .with_rule(SubNegConst)
+    }
+
+    /// Creates an instruction combiner without any rules
+    pub fn empty() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// Adds the rule (rules are tried in the order they were added)
+    pub fn with_rule<T: CombineRule + 'static>(mut self, rule: T) -> Self {
+        self.rules.push(Arc::new(rule));
+        self
+    }
+
+    /// Returns the names of the rules
+    pub fn rules(&self) -> Vec<&'static str> {
+        self.rules.iter().map(|rule| rule.name()).collect()
+    }
+
+    /// Applies the rules to the operand until none of them applies anymore
+    fn combine(&self, op: &IrOperand) -> Option<IrOperand> {
+        let mut current = op.clone();
+
+        for _ in 0..InstCombine::MAX_REWRITES {
+            let Some(next) = self.rules.iter().find_map(|rule| rule.apply(&current)) else {
+                break;
+            };
+
+            current = next;
+        }
+
+        (current != *op).then_some(current)
+    }
+}
+
+impl Optimization for InstCombine {
+    fn name(&self) -> &'static str {
+        "Instruction Combining"
+    }
+
+    fn preserved(&self) -> PreservedAnalyses {
+        // the rewrites create new nodes (which the dominator tree wouldn't know),
+        // but they only replace additions and subtractions, so the calls stay
+        PreservedAnalyses::none().preserve::<CallGraph>()
+    }
+
+    fn run(&self, func: &mut Function, _analyses: &mut AnalysisManager) -> bool {
+        // maps the ids of the nodes to their replacement, so every user shares it
+        let mut combined: HashMap<usize, Option<IrOperand>> = HashMap::new();
+        let mut changed = false;
+
+        for node in walk_nodes(&func.ir) {
+            let mut node = node.borrow_mut();
+
+            for op in node.ops.iter_mut() {
+                let Some(used) = op.node() else {
+                    continue;
+                };
+
+                let replacement = combined
+                    .entry(used.id())
+                    .or_insert_with(|| self.combine(op))
+                    .clone();
+
+                if let Some(replacement) = replacement {
+                    *op = replacement;
+                    changed = true;
+                }
+            }
+        }
+
+        // the rewritten nodes at the top level aren't used anymore
+        let len = func.ir.len();
+        func.ir.retain(|op| {
+            op.node().is_none_or(|node| {
+                combined
+                    .entry(node.id())
+                    .or_insert_with(|| self.combine(op))
+                    .is_none()
+            })
+        });
+
+        changed || func.ir.len() != len
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{
+    codegen::{ISelStrategy, RegAllocStrategy},
+    opt::{ConstFold, CopyProp, Dce, GlobalDce, Gvn, InstCombine, Pipeline},
+};
+
+/// Optimization level presets
+///
+/// A level picks the pass pipeline and the strategies of the register allocator
+/// and the instruction selector
+///
+/// Example:
+/// ```rust
+/// use jacob::codegen::{ISelStrategy, RegAllocStrategy};
+/// use jacob::opt::OptLevel;
+///
+/// assert!(OptLevel::O0.pipeline().is_empty());
+/// assert_eq!(OptLevel::default(), OptLevel::O1);
+/// assert_eq!(OptLevel::O2.regalloc_strategy(), RegAllocStrategy::ReuseOperands);
+/// assert_eq!(OptLevel::Os.isel_strategy(), ISelStrategy::Peephole);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum OptLevel {
+    /// No optimizations
+    O0,
+    /// Cheap optimizations
+    #[default]
+    O1,
+    /// Most optimizations
+    O2,
+    /// All optimizations (currently the same as `O2`)
+    O3,
+    /// Optimize for size, like `O2` but without global value numbering
+    /// (merged values live longer, which can require more saved registers and spills)
+    Os,
+    /// Optimize aggressively for size (currently the same as `Os`)
+    Oz,
+}
+
+impl OptLevel {
+    /// Returns the pass pipeline of the level
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::opt::OptLevel;
+    ///
+    /// assert!(OptLevel::O2.pipeline().passes().contains(&"Global Value Numbering"));
+    /// assert!(!OptLevel::Os.pipeline().passes().contains(&"Global Value Numbering"));
+    /// ```
+    pub fn pipeline(&self) -> Pipeline {
+        if *self == OptLevel::O0 {
+            return Pipeline::new();
+        }
+
+        let mut simplify = Pipeline::new()
+            .with_pass(CopyProp {})
+            .with_pass(ConstFold {})
+            .with_pass(InstCombine::new());
+
+        if *self == OptLevel::O1 {
+            return simplify.with_pass(Dce {}).with_pass(GlobalDce {});
+        }
+
+        if !self.optimizes_for_size() {
+            simplify = simplify.with_pass(Gvn {});
+        }
+
+        Pipeline::new()
+            .with_fixpoint(simplify, Pipeline::DEFAULT_MAX_ITERATIONS)
+            .with_pass(Dce {})
+            .with_pass(GlobalDce {})
+    }
+
+    /// Returns the register allocation strategy of the level
+    pub fn regalloc_strategy(&self) -> RegAllocStrategy {
+        match self {
+            OptLevel::O0 | OptLevel::O1 => RegAllocStrategy::Fast,
+            _ => RegAllocStrategy::ReuseOperands,
+        }
+    }
+
+    /// Returns if the register allocator coalesces copies
+    pub fn coalesces_copies(&self) -> bool {
+        *self != OptLevel::O0
+    }
+
+    /// Returns the instruction selection strategy of the level
+    pub fn isel_strategy(&self) -> ISelStrategy {
+        match self {
+            OptLevel::O0 | OptLevel::O1 => ISelStrategy::Direct,
+            _ => ISelStrategy::Peephole,
+        }
+    }
+
+    /// Returns if the level prefers small code over fast code
+    pub fn optimizes_for_size(&self) -> bool {
+        matches!(self, OptLevel::Os | OptLevel::Oz)
+    }
+}
@@ -2,23 +2,90 @@
 
 use std::any::Any;
 
-use crate::ir::Function;
+use crate::ir::{Function, FunctionAttribute, Module};
 
+/// Cached analyses
+pub mod analysis;
+/// Constant folding
+pub mod constfold;
+/// Copy propagation
+pub mod copyprop;
 /// Dead code elemination
 pub mod dce;
+/// Dead function elimination
+pub mod globaldce;
+/// Global value numbering
+pub mod gvn;
+/// Instruction combining
+pub mod instcombine;
+/// Optimization level presets
+pub mod level;
+/// Ordered pass pipelines
+pub mod pipeline;
 
+pub use analysis::*;
+pub use constfold::*;
+pub use copyprop::*;
 pub use dce::*;
+pub use globaldce::*;
+pub use gvn::*;
+pub use instcombine::*;
+pub use level::*;
+pub use pipeline::*;
 
 /// Trait to implement to make an optimization pass
-pub trait Optimization: Any {
+pub trait Optimization: Any + Send + Sync {
     /// Returns the name of the optimization
     fn name(&self) -> &'static str;
 
-    /// Runs the optimization pass on a singular function
-    fn run(&self, func: &mut Function);
+    /// Runs the optimization pass on a singular function and returns if it changed something
+    fn run(&self, func: &mut Function, analyses: &mut AnalysisManager) -> bool;
 
-    /*
-    /// Runs the optimization pass on an entire module
-    fn run_module(&self, module: &mut Module);
-    */
+    /// Returns the analyses which are still valid after the pass changed the ir
+    ///
+    /// Example:
+    /// ```rust
+    /// use jacob::ir::{Function, IrOperand, Module, TypeMetadata};
+    /// use jacob::opt::{AnalysisManager, ConstFold, Dominators, Gvn, Pipeline};
+    ///
+    /// let mut func = Function::new("func");
+    /// let x = func.add_arg(TypeMetadata::Int64);
+    /// let one = IrOperand::ConstNum { num: 1, ty: TypeMetadata::Int64 };
+    /// let two = func.add(&one, &one);
+    /// let out = func.add(&x, &two);
+    /// func.ret(&out);
+    ///
+    /// let mut module = Module::new();
+    /// module.add_func(func);
+    ///
+    /// let mut analyses = AnalysisManager::new();
+    /// analyses.get::<Dominators>(&module.funcs[0]);
+    ///
+    /// // constant folding keeps the dominators, so gvn takes them from the cache
+    /// let pipeline = Pipeline::new().with_pass(ConstFold {}).with_pass(Gvn {});
+    /// assert!(pipeline.run_with(&mut module, &mut analyses));
+    /// assert_eq!(analyses.computed(), 1);
+    /// ```
+    fn preserved(&self) -> PreservedAnalyses {
+        PreservedAnalyses::none()
+    }
+
+    /// Runs the optimization pass on an entire module and returns if it changed something
+    ///
+    /// By default the pass runs on every function (except naked ones, which are
+    /// emitted exactly like they were written). Module passes override this and
+    /// invalidate the analyses of the functions they changed
+    fn run_module(&self, module: &mut Module, analyses: &mut AnalysisManager) -> bool {
+        let mut changed = false;
+
+        for func in &mut module.funcs {
+            if !func.has_attr(&FunctionAttribute::Naked) && self.run(func, analyses) {
+                analyses.invalidate(func, &self.preserved());
+                func.own_nodes();
+                changed = true;
+            }
+        }
+
+        changed
+    }
 }
@@ -0,0 +1,150 @@
+use std::sync::Arc;
+
+use crate::{
+    ir::Module,
+    opt::{AnalysisManager, Optimization},
+};
+
+/// A step of a pipeline
+#[derive(Clone)]
+enum Step {
+    /// Runs the pass once
+    Pass(Arc<dyn Optimization>),
+    /// Repeats the inner pipeline until it doesn't change anything anymore
+    Fixpoint {
+        pipeline: Pipeline,
+        max_iterations: usize,
+    },
+}
+
+/// An ordered list of optimization passes
+///
+/// The passes run in the order they were added. Groups of passes which
+/// enable each other can be repeated until nothing changes anymore
+///
+/// Example:
+/// ```rust
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use jacob::ir::{Function, Module};
+/// use jacob::opt::{AnalysisManager, Dce, Optimization, Pipeline};
+///
+/// static RUNS: AtomicUsize = AtomicUsize::new(0);
+///
+/// /// Pretends to change the module until it ran 3 times
+/// struct CountDown;
+///
+/// impl Optimization for CountDown {
+///     fn name(&self) -> &'static str {
+///         "Count Down"
+///     }
+///
+///     fn run(&self, _func: &mut Function, _analyses: &mut AnalysisManager) -> bool {
+///         false
+///     }
+///
+///     fn run_module(&self, _module: &mut Module, _analyses: &mut AnalysisManager) -> bool {
+///         RUNS.fetch_add(1, Ordering::Relaxed) < 2
+///     }
+/// }
+///
+/// let pipeline = Pipeline::new()
+///     .with_pass(Dce {})
+///     .with_fixpoint(Pipeline::new().with_pass(CountDown), 10);
+///
+/// assert_eq!(pipeline.passes(), vec!["Dead Code Elimination", "Count Down"]);
+///
+/// let mut module = Module::new();
+/// assert!(module.run_pipeline(&pipeline));
+/// assert_eq!(RUNS.load(Ordering::Relaxed), 3);
+/// ```
+#[derive(Clone, Default)]
+pub struct Pipeline {
+    steps: Vec<Step>,
+}
+
+impl Pipeline {
+    /// The default maximal number of iterations of a fixpoint group
+    pub const DEFAULT_MAX_ITERATIONS: usize = 16;
+
+    /// Creates a new empty pipeline
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends the pass to the pipeline
+    pub fn with_pass<T: Optimization>(mut self, opt: T) -> Self {
+        self.steps.push(Step::Pass(Arc::new(opt)));
+        self
+    }
+
+    /// Appends an already shared pass to the pipeline
+    pub fn with_shared_pass(mut self, opt: Arc<dyn Optimization>) -> Self {
+        self.steps.push(Step::Pass(opt));
+        self
+    }
+
+    /// Appends a group which is repeated until it doesn't change the module anymore
+    /// (but at most `max_iterations` times)
+    pub fn with_fixpoint(mut self, pipeline: Pipeline, max_iterations: usize) -> Self {
+        self.steps.push(Step::Fixpoint {
+            pipeline,
+            max_iterations,
+        });
+        self
+    }
+
+    /// Returns the names of all passes in the order they are run in
+    /// (passes in fixpoint groups are only listed once)
+    pub fn passes(&self) -> Vec<&'static str> {
+        let mut names = Vec::new();
+
+        for step in &self.steps {
+            match step {
+                Step::Pass(opt) => names.push(opt.name()),
+                Step::Fixpoint { pipeline, .. } => names.extend(pipeline.passes()),
+            }
+        }
+
+        names
+    }
+
+    /// Returns if the pipeline contains no passes
+    pub fn is_empty(&self) -> bool {
+        self.passes().is_empty()
+    }
+
+    /// Runs the pipeline on the module and returns if it changed something
+    pub fn run(&self, module: &mut Module) -> bool {
+        self.run_with(module, &mut AnalysisManager::new())
+    }
+
+    /// Runs the pipeline on the module sharing the analysis cache between the passes
+    pub fn run_with(&self, module: &mut Module, analyses: &mut AnalysisManager) -> bool {
+        let mut changed = false;
+
+        for step in &self.steps {
+            match step {
+                Step::Pass(opt) => {
+                    if opt.run_module(module, analyses) {
+                        analyses.invalidate_module(&opt.preserved());
+                        changed = true;
+                    }
+                }
+                Step::Fixpoint {
+                    pipeline,
+                    max_iterations,
+                } => {
+                    for _ in 0..*max_iterations {
+                        if !pipeline.run_with(module, analyses) {
+                            break;
+                        }
+
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        changed
+    }
+}
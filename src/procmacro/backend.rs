@@ -3,7 +3,7 @@ use proc_macro::TokenStream;
 use quote::{format_ident, quote};
 use syn::{Expr, ExprArray, Ident, Lit, LitInt, Token, braced, parse::Parse, parse_macro_input};
 
-const BACKEND_FIELDS: usize = 8;
+const BACKEND_FIELDS: usize = 9;
 
 #[derive(Debug, Default)]
 pub struct RegArgMap {
@@ -15,6 +15,7 @@ struct BackendInput {
     name: Option<Ident>,
     ret_reg: Option<Ident>,
     stack_reg: Option<Ident>,
+    data_layout: Option<Ident>,
 
     caller_saved: Vec<Ident>,
     callee_saved: Vec<Ident>,
@@ -62,6 +63,7 @@ impl Parse for BackendInput {
             name: None,
             ret_reg: None,
             stack_reg: None,
+            data_layout: None,
             caller_saved: Vec::new(),
             callee_saved: Vec::new(),
             gprs: Vec::new(),
@@ -123,8 +125,9 @@ impl Parse for BackendInput {
                     "name" => out.name = Some(ident),
                     "ret_reg" => out.ret_reg = Some(ident),
                     "stack_reg" => out.stack_reg = Some(ident),
+                    "data_layout" => out.data_layout = Some(ident),
                     _ => panic!(
-                        "Standalone idents are only supported for the fields: `name`, `ret_reg`, `stack_reg`"
+                        "Standalone idents are only supported for the fields: `name`, `ret_reg`, `stack_reg`, `data_layout`"
                     ),
                 }
             }
@@ -156,6 +159,9 @@ pub fn backend_impl(input: TokenStream) -> TokenStream {
     let Some(sp_reg) = def.stack_reg else {
         panic!("expected stack register")
     };
+    let Some(data_layout) = def.data_layout else {
+        panic!("expected data layout")
+    };
     let name_str = name.to_string();
     let struct_name = format_ident!("{}Backend", name);
     let reg_name = format_ident!("{}Reg", name);
@@ -178,7 +184,7 @@ pub fn backend_impl(input: TokenStream) -> TokenStream {
         .arg_reg_map
         .map
         .iter()
-        .map(|(num, val)| quote! { val if val == #val.id() => #num })
+        .map(|(num, val)| quote! { val if val == ::jacob::codegen::Reg::id(&#val) => #num })
         .collect();
 
     let mut reg_consts: Vec<proc_macro2::TokenStream> = gpr_regs
@@ -202,13 +208,13 @@ pub fn backend_impl(input: TokenStream) -> TokenStream {
         .iter()
         .map(|reg| {
             let name = reg.to_string();
-            quote! { val if val == #reg.id() => #name }
+            quote! { val if val == ::jacob::codegen::Reg::id(&#reg) => #name }
         })
         .collect();
     let caller_regs_iter: Vec<proc_macro2::TokenStream> = caller_regs
         .iter()
         .map(|x| {
-            quote! { val if val == #x.id() => true }
+            quote! { val if val == ::jacob::codegen::Reg::id(&#x) => true }
         })
         .collect();
 
@@ -217,73 +223,77 @@ pub fn backend_impl(input: TokenStream) -> TokenStream {
         #[derive(Debug, Clone, PartialEq, Eq)]
         pub struct #struct_name {}
 
-        impl ArchBackend for #struct_name {}
+        impl ::jacob::codegen::ArchBackend for #struct_name {}
 
-        impl ArchInfos for #struct_name {
+        impl ::jacob::codegen::ArchInfos for #struct_name {
             fn name(&self) -> &'static str {
                 #name_str
             }
 
-            fn caller_gpr(&self) -> Vec<Box<dyn crate::codegen::Reg>> {
+            fn caller_gpr(&self) -> Vec<Box<dyn ::jacob::codegen::Reg>> {
                 let regs: Vec<#reg_name> = vec![#(#caller_regs,)*];
                 regs.iter()
-                    .map(|x| Box::new(*x) as Box<dyn crate::codegen::Reg>)
+                    .map(|x| Box::new(*x) as Box<dyn ::jacob::codegen::Reg>)
                     .collect()
             }
 
-            fn callee_gpr(&self) -> Vec<Box<dyn crate::codegen::Reg>> {
+            fn callee_gpr(&self) -> Vec<Box<dyn ::jacob::codegen::Reg>> {
                 let regs: Vec<#reg_name> = vec![#(#callee_regs,)*];
                 regs.iter()
-                    .map(|x| Box::new(*x) as Box<dyn crate::codegen::Reg>)
+                    .map(|x| Box::new(*x) as Box<dyn ::jacob::codegen::Reg>)
                     .collect()
             }
 
 
-            fn grps(&self) -> Vec<Box<dyn crate::codegen::Reg>> {
+            fn grps(&self) -> Vec<Box<dyn ::jacob::codegen::Reg>> {
                 let regs: Vec<#reg_name> = vec![#(#gpr_regs,)*];
                 regs.iter()
-                    .map(|x| Box::new(*x) as Box<dyn crate::codegen::Reg>)
+                    .map(|x| Box::new(*x) as Box<dyn ::jacob::codegen::Reg>)
                     .collect()
             }
 
-            fn ret_reg(&self) -> crate::codegen::Allocation {
-                #ret_reg.alloc()
+            fn ret_reg(&self) -> ::jacob::codegen::Allocation {
+                ::jacob::codegen::Reg::alloc(&#ret_reg)
+            }
+
+            fn get_stack_ptr(&self) -> ::jacob::codegen::Allocation {
+                ::jacob::codegen::Reg::alloc(&#sp_reg)
             }
 
-            fn get_stack_ptr(&self) -> Allocation {
-                #sp_reg.alloc()
+            fn data_layout(&self) -> ::jacob::codegen::DataLayout {
+                #data_layout
             }
 
             fn callconv_argpos(
                 &self,
                 num: usize,
-                ty: crate::ir::TypeMetadata,
-            ) -> crate::codegen::Allocation {
+                ty: ::jacob::ir::TypeMetadata,
+            ) -> ::jacob::codegen::Allocation {
                 if num < #reg_args {
-                    return Allocation::Register {
+                    return ::jacob::codegen::Allocation::Register {
                         id: match num {
                             #(#reg_map,)*
                             _ => unreachable!(),
                         }
-                        .id(),
+                        .id,
                         ty,
                     };
                 }
 
-                Allocation::Stack { slot: (num as isize + #stack_off) as usize, ty } // ToDo: find out: why 5?
+                ::jacob::codegen::Allocation::Stack { slot: (num as isize + #stack_off) as usize, ty } // ToDo: find out: why 5?
             }
         }
 
-        impl BackendDecompiler for #struct_name {
-            fn num_for_arg(&self, op: &Allocation) -> usize {
-                if let Allocation::Register { id, .. } = op {
+        impl ::jacob::codegen::BackendDecompiler for #struct_name {
+            fn num_for_arg(&self, op: &::jacob::codegen::Allocation) -> usize {
+                if let ::jacob::codegen::Allocation::Register { id, .. } = op {
                     return match *id {
                         #(#reg_map_rev,)*
                         _ => panic!("Given register (id: {id}) cannot be an argument"),
                     };
                 }
 
-                if let Allocation::Stack { slot, .. } = op {
+                if let ::jacob::codegen::Allocation::Stack { slot, .. } = op {
                     return (*slot as isize + #stack_off) as usize;
                 }
 
@@ -299,13 +309,13 @@ pub fn backend_impl(input: TokenStream) -> TokenStream {
 
         #(#reg_consts)*
 
-        impl Reg for #reg_name {
+        impl ::jacob::codegen::Reg for #reg_name {
             fn id(&self) -> usize {
                 self.id
             }
 
-            fn ty(&self) -> crate::ir::TypeMetadata {
-                crate::ir::TypeMetadata::Int64
+            fn ty(&self) -> ::jacob::ir::TypeMetadata {
+                ::jacob::ir::TypeMetadata::Int64
             }
 
             fn name(&self) -> &'static str {
@@ -320,7 +330,7 @@ pub fn backend_impl(input: TokenStream) -> TokenStream {
             }
 
             fn caller_saved(&self) -> bool {
-                match self.id() {
+                match self.id {
                     #(#caller_regs_iter, )*
                     _ => false,
                 }
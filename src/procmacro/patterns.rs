@@ -16,6 +16,7 @@ enum Pos {
     Mem,
     Any,
     Imm,
+    ConstUse,
 }
 
 impl Pos {
@@ -25,6 +26,7 @@ impl Pos {
             Pos::Mem => format_ident!("is_mem"),
             Pos::Imm => format_ident!("is_imm"),
             Pos::Any => format_ident!("is_any"),
+            Pos::ConstUse => format_ident!("is_const_use"),
         }
     }
 }
@@ -45,7 +47,10 @@ impl Parse for Pattern {
                 "imm" => Pos::Imm,
                 "mem" => Pos::Mem,
                 "any" => Pos::Any,
-                inv => panic!("Invalid position: {inv}. Available are: gr, imm, mem"),
+                "constuse" => Pos::ConstUse,
+                inv => {
+                    panic!("Invalid position: {inv}. Available are: gr, imm, mem, any, constuse")
+                }
             }
         };
 
@@ -132,11 +137,11 @@ impl VisitMut for Rewriter {
         {
             let name = p.path.segments[0].ident.to_string();
             let replacement = match name.as_str() {
-                "in1" => Some(quote!(inst.ops[0])),
-                "in2" => Some(quote!(inst.ops[1])),
-                "in3" => Some(quote!(inst.ops[2])),
-                "in4" => Some(quote!(inst.ops[3])),
-                "out" => Some(quote!(inst.alloc.unwrap())),
+                "in1" => Some(quote!(inst.ops()[0])),
+                "in2" => Some(quote!(inst.ops()[1])),
+                "in3" => Some(quote!(inst.ops()[2])),
+                "in4" => Some(quote!(inst.ops()[3])),
+                "out" => Some(quote!(inst.alloc().unwrap())),
                 _ => None,
             };
             if let Some(rep) = replacement {
@@ -163,13 +168,13 @@ impl VisitMut for RewriterAsm {
                 panic!("No function name was supplyed")
             };
 
-            call.func = Box::new(syn::parse_quote!(crate::codegen::AssemblyInst::withn));
+            *call.func = syn::parse_quote!(::jacob::codegen::AssemblyInst::withn);
 
             let name_lit = syn::LitStr::new(&name, proc_macro2::Span::call_site());
             let args_vec: Vec<Expr> = call.args.iter().cloned().collect();
 
             let new_expr: Expr = syn::parse_quote! {
-                crate::codegen::AssemblyInst::withn(#name_lit, vec![#(&#args_vec),*])
+                ::jacob::codegen::AssemblyInst::withn(#name_lit, vec![#(&#args_vec),*])
             };
 
             *expr = new_expr;
@@ -231,12 +236,12 @@ pub(crate) fn patterns_impl(input: TokenStream) -> TokenStream {
         let arms = pats.iter().map(|p| {
             let ins_check = p.ins.iter().enumerate().map(|(index, pos)| {
                 let func_ident = pos.func();
-                quote! { && inst.ops[#index].#func_ident() }
+                quote! { && inst.ops()[#index].#func_ident() }
             });
 
             let out_check = if let Some(out) = p.out {
                 let func_ident = out.func();
-                quote! { && inst.has_out && inst.alloc.as_ref().unwrap().#func_ident() }
+                quote! { && inst.has_out() && inst.alloc().unwrap().#func_ident() }
             } else {
                 quote! {}
             };
@@ -267,7 +272,7 @@ pub(crate) fn patterns_impl(input: TokenStream) -> TokenStream {
         });
 
         quote! {
-            crate::ir::IrOpcode::#name_ident => {
+            ::jacob::ir::IrOpcode::#name_ident => {
                 #(#arms)*
                 panic!("no matching pattern for {:?}", inst);
             }
@@ -284,7 +289,7 @@ pub(crate) fn patterns_impl(input: TokenStream) -> TokenStream {
         for (index, asm) in pat.asm.iter().enumerate() {
             let name = force_call_name(asm);
 
-            asm_cond = quote! { #asm_cond && asm[#index].opcode == #name }
+            asm_cond = quote! { #asm_cond && asm[#index].opcode() == #name }
         }
 
         let mut ops_cond = quote! {};
@@ -298,26 +303,31 @@ pub(crate) fn patterns_impl(input: TokenStream) -> TokenStream {
                     let Expr::Path(path) = arg else {
                         if let Expr::MethodCall(c) = arg {
                             // e.g: RAX.alloc()
-                            ops_cond = quote! { #ops_cond && asm[#asm_index].ops[#op_index] == #c}
+                            ops_cond = quote! { #ops_cond && asm[#asm_index].ops()[#op_index] == #c}
                         }
                         continue;
                     };
 
                     match extract_name_from_path(path).as_str() {
                         "in1" => {
-                            ops_cond = quote! { #ops_cond && asm[#asm_index].ops[#op_index].#func()}
+                            ops_cond =
+                                quote! { #ops_cond && asm[#asm_index].ops()[#op_index].#func()}
                         }
                         "in2" => {
-                            ops_cond = quote! { #ops_cond && asm[#asm_index].ops[#op_index].#func()}
+                            ops_cond =
+                                quote! { #ops_cond && asm[#asm_index].ops()[#op_index].#func()}
                         }
                         "in3" => {
-                            ops_cond = quote! { #ops_cond && asm[#asm_index].ops[#op_index].#func()}
+                            ops_cond =
+                                quote! { #ops_cond && asm[#asm_index].ops()[#op_index].#func()}
                         }
                         "in4" => {
-                            ops_cond = quote! { #ops_cond && asm[#asm_index].ops[#op_index].#func()}
+                            ops_cond =
+                                quote! { #ops_cond && asm[#asm_index].ops()[#op_index].#func()}
                         }
                         "out" => {
-                            ops_cond = quote! { #ops_cond && asm[#asm_index].ops[#op_index].#func()}
+                            ops_cond =
+                                quote! { #ops_cond && asm[#asm_index].ops()[#op_index].#func()}
                         }
                         _ => panic!(),
                     }
@@ -343,8 +353,8 @@ pub(crate) fn patterns_impl(input: TokenStream) -> TokenStream {
                 let path = extract_name_from_path(path);
 
                 if &path == "out" {
-                    alloc = quote! { Some(asm[#index].ops[#i]) };
-                    ty = quote! { Some(asm[#index].ops[#i].get_ty()) };
+                    alloc = quote! { Some(asm[#index].ops()[#i]) };
+                    ty = quote! { Some(asm[#index].ops()[#i].get_ty()) };
                     has_out = quote! { true };
                     continue;
                 }
@@ -355,10 +365,10 @@ pub(crate) fn patterns_impl(input: TokenStream) -> TokenStream {
                     ops = quote! { #ops , };
                 }
                 match path.as_str() {
-                    "in1" => ops = quote! { #ops asm[#index].ops[#i] },
-                    "in2" => ops = quote! { #ops asm[#index].ops[#i] },
-                    "in3" => ops = quote! { #ops asm[#index].ops[#i] },
-                    "in4" => ops = quote! { #ops asm[#index].ops[#i] },
+                    "in1" => ops = quote! { #ops asm[#index].ops()[#i] },
+                    "in2" => ops = quote! { #ops asm[#index].ops()[#i] },
+                    "in3" => ops = quote! { #ops asm[#index].ops()[#i] },
+                    "in4" => ops = quote! { #ops asm[#index].ops()[#i] },
                     _ => {
                         parsed_ops -= 1;
                     }
@@ -368,27 +378,27 @@ pub(crate) fn patterns_impl(input: TokenStream) -> TokenStream {
 
         quote! {
             if asm.len() >= #asm_len #asm_cond #ops_cond {
-                let inst = crate::codegen::AllocatedIrNode {
-                    opcode: crate::ir::IrOpcode::#opcode,
-                    ops: vec![#ops],
-                    has_out: #has_out,
-                    ty: #ty,
-                    alloc: #alloc,
-                };
+                let inst = ::jacob::codegen::AllocatedIrNode::new(
+                    ::jacob::ir::IrOpcode::#opcode,
+                    vec![#ops],
+                    #has_out,
+                    #ty,
+                    #alloc,
+                );
                 return (#asm_len, inst);
             }
         }
     });
 
     quote! {
-        fn lower_inst(&self, inst: &crate::codegen::AllocatedIrNode) -> Vec<crate::codegen::AssemblyInst> {
-            match inst.opcode {
+        fn lower_inst(&self, inst: &::jacob::codegen::AllocatedIrNode) -> Vec<::jacob::codegen::AssemblyInst> {
+            match inst.opcode() {
                 #(#lower_inst_match)*
                 unhandled => todo!("Implement handling for {:?}", unhandled),
             }
         }
 
-        fn disasm_inst(&self, asm: &[crate::codegen::AssemblyInst]) -> (usize, crate::codegen::AllocatedIrNode) {
+        fn disasm_inst(&self, asm: &[::jacob::codegen::AssemblyInst]) -> (usize, ::jacob::codegen::AllocatedIrNode) {
             if asm.is_empty() {
                 panic!("Given assembly instructions are empty")
             }
@@ -8,10 +8,11 @@ use proc_macro::TokenStream;
 /// This procedual macro is used to build backend compilation support!
 ///
 /// It automaticlly implements compilation and decompilation from the specified
-/// patterns
+/// patterns. The generated code only uses `::jacob::...` paths, so it also works
+/// in other crates (through `jacob::codegen::patterns`)
 ///
 /// Example:
-/// ```rust no-run
+/// ```rust ignore
 /// impl BackendInst for X86Backend {
 ///    patterns! {
 ///        Add(Gr, Gr) -> Gr {
@@ -38,7 +39,8 @@ pub fn patterns(input: TokenStream) -> TokenStream {
 /// This procmacro is used to define a backend
 ///
 /// It automaticlly implemenets registers, you only need to add support for
-/// asmprinting and compilation/decompilation (we reconmend the `patterns macro`)
+/// asmprinting and compilation/decompilation (we reconmend the `patterns macro`).
+/// The `data_layout` field takes a `DataLayout` constant which is in scope
 #[proc_macro]
 pub fn backend(input: TokenStream) -> TokenStream {
     backend::backend_impl(input)
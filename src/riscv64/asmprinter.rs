@@ -1,4 +1,7 @@
-use crate::{codegen::AsmPrinter, riscv64::Riscv64Backend};
+use crate::{
+    codegen::{ArchInfos, AsmPrinter, constpool::const_label},
+    riscv64::Riscv64Backend,
+};
 
 macro_rules! reg_printer {
     ($num_var:tt, $ty_var:tt, $num_lit:literal, $b64_name:expr) => {
@@ -15,9 +18,11 @@ impl AsmPrinter for Riscv64Backend {
     fn print_op(&self, op: &crate::codegen::Allocation) -> String {
         match op {
             crate::codegen::Allocation::Register { id, ty } => self.print_reg(id, ty),
-            crate::codegen::Allocation::Stack { slot, ty: _ } => format!("[sp, #-{}]!", slot * 16), // ToDo: check alignment
-            crate::codegen::Allocation::Imm { num, ty: _ } => format!("#{num:x}"),
-            crate::codegen::Allocation::ConstUse { id } => format!("[c{id}]"),
+            crate::codegen::Allocation::Stack { slot, ty: _ } => {
+                format!("{}(sp)", self.data_layout().slot_offset(*slot))
+            }
+            crate::codegen::Allocation::Imm { num, ty: _ } => format!("{num:#x}"),
+            crate::codegen::Allocation::ConstUse { id } => const_label(*id),
         }
     }
 
@@ -52,15 +57,37 @@ impl AsmPrinter for Riscv64Backend {
         panic!("Impossible register id: {num}. RiscV supports 0-25");
     }
 
-    fn print_const(&self, c: &crate::codegen::Constant) -> String {
-        format!("c{}: {:?}", c.id, c.bytes)
+    fn print_prologue(&self, func: &crate::codegen::FuncAsm) -> String {
+        if func.frame_size == 0 {
+            return String::new();
+        }
+
+        let mut out = format!("\taddi sp, sp, -{}\n", func.frame_size);
+        for (reg, slot) in &func.saved_regs {
+            out += &format!("\tsd {}, {}\n", self.print_op(reg), self.print_op(slot));
+        }
+        if let Some(slot) = &func.link_slot {
+            out += &format!("\tsd ra, {}\n", self.print_op(slot));
+        }
+        out
     }
 
-    fn print_code_section(&self) -> &'static str {
-        ".text\n"
+    fn print_epilogue(&self, func: &crate::codegen::FuncAsm) -> String {
+        if func.frame_size == 0 {
+            return String::new();
+        }
+
+        let mut out = String::new();
+        if let Some(slot) = &func.link_slot {
+            out += &format!("\tld ra, {}\n", self.print_op(slot));
+        }
+        for (reg, slot) in &func.saved_regs {
+            out += &format!("\tld {}, {}\n", self.print_op(reg), self.print_op(slot));
+        }
+        out + &format!("\taddi sp, sp, {}\n", func.frame_size)
     }
 
-    fn print_global(&self, func: &String) -> String {
-        format!(".globl {}\n", func)
+    fn print_code_section(&self) -> &'static str {
+        ".text\n"
     }
 }
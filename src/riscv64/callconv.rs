@@ -0,0 +1,44 @@
+use crate::{
+    codegen::{CallConvInfos, CallLowering, Reg},
+    ir::CallingConv,
+    riscv64::*,
+};
+
+fn regs(regs: &[Riscv64Reg]) -> Vec<Box<dyn Reg>> {
+    regs.iter().map(|x| Box::new(*x) as Box<dyn Reg>).collect()
+}
+
+impl CallConvInfos for Riscv64Backend {
+    fn callconv_arg_regs(&self, conv: CallingConv) -> Option<Vec<Box<dyn Reg>>> {
+        match conv {
+            CallingConv::C | CallingConv::RiscvLp64 | CallingConv::PreserveMost => {
+                Some(regs(&[A0, A1, A2, A3, A4, A5, A6, A7]))
+            }
+            CallingConv::Fast => Some(regs(&[A0, A1, A2, A3, A4, A5, A6, A7, T3, T4, T5, T6])),
+            CallingConv::SysV | CallingConv::Win64 | CallingConv::Aapcs64 => None,
+        }
+    }
+
+    fn callconv_callee_saved(&self, conv: CallingConv) -> Option<Vec<Box<dyn Reg>>> {
+        match conv {
+            CallingConv::C | CallingConv::RiscvLp64 | CallingConv::Fast => {
+                Some(regs(&[S2, S3, S4, S5, S6, S7, S8, S9, S10, S11]))
+            }
+            // only the argument registers are clobbered
+            CallingConv::PreserveMost => Some(regs(&[
+                T3, T4, T5, T6, S2, S3, S4, S5, S6, S7, S8, S9, S10, S11,
+            ])),
+            CallingConv::SysV | CallingConv::Win64 | CallingConv::Aapcs64 => None,
+        }
+    }
+}
+
+impl CallLowering for Riscv64Backend {
+    fn call_opcode(&self) -> &'static str {
+        "call"
+    }
+
+    fn call_uses_link_reg(&self) -> bool {
+        true
+    }
+}
@@ -1,7 +1,7 @@
 use procmacro::patterns;
 
 use crate::{
-    codegen::{BackendInst, InstrincLowering, Reg},
+    codegen::{AssemblyInst, BackendInst, InstrincLowering, Reg},
     riscv64::{A0, Riscv64Backend},
 };
 
@@ -10,9 +10,24 @@ impl BackendInst for Riscv64Backend {
         Add(Gr, Gr) -> Gr {
             asm: add (out, in1, in2)
         }
+        Add(Gr, Imm) -> Gr {
+            asm: addi (out, in1, in2)
+        }
         Copy(Gr) -> Gr {
             asm: mv (out, in1)
         }
+        Copy(Imm) -> Gr {
+            asm: li (out, in1)
+        }
+        Copy(ConstUse) -> Gr {
+            asm: ld (out, in1)
+        }
+        Copy(Gr) -> Mem {
+            asm: sd (in1, out)
+        }
+        Copy(Mem) -> Gr {
+            asm: ld (out, in1)
+        }
         Ret(Gr) {
             condition: in1 == A0.alloc()
             asm: ret()
@@ -23,6 +38,14 @@ impl BackendInst for Riscv64Backend {
             asm: ret()
         }
     }
+
+    fn is_legal_imm(&self, num: usize, _ty: crate::ir::TypeMetadata) -> bool {
+        num < 2048
+    }
+
+    fn is_noop(&self, inst: &AssemblyInst) -> bool {
+        inst.opcode() == "mv" && inst.ops().len() == 2 && inst.ops()[0] == inst.ops()[1]
+    }
 }
 
 impl InstrincLowering for Riscv64Backend {}
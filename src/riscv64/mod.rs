@@ -2,15 +2,19 @@
 
 use procmacro::backend;
 
-use crate::codegen::{Allocation, ArchBackend, ArchInfos, BackendDecompiler, Reg};
+use crate::codegen::{DataLayout, DebugInfos, Reg};
 
 mod asmprinter;
+mod callconv;
 mod lowering;
 
+const LAYOUT: DataLayout = DataLayout::LP64;
+
 backend! {
     name: Riscv64,
     ret_reg: A0,
     stack_reg: SP,
+    data_layout: LAYOUT,
 
     caller_saved: [ A0, A1, A2, A3, A4, A5, A6, A7, T3, T4, T5, T6, T7 ],
     callee_saved: [
@@ -35,3 +39,41 @@ backend! {
 
     stack_off: -5,
 }
+
+impl DebugInfos for Riscv64Backend {
+    fn dwarf_reg(&self, id: usize) -> Option<u16> {
+        let regs = [
+            (A0, 10),
+            (A1, 11),
+            (A2, 12),
+            (A3, 13),
+            (A4, 14),
+            (A5, 15),
+            (A6, 16),
+            (A7, 17),
+            (S2, 18),
+            (S3, 19),
+            (S4, 20),
+            (S5, 21),
+            (S6, 22),
+            (S7, 23),
+            (S8, 24),
+            (S9, 25),
+            (S10, 26),
+            (S11, 27),
+            (T3, 28),
+            (T4, 29),
+            (T5, 30),
+            (T6, 31),
+            (SP, 2),
+        ];
+
+        regs.iter()
+            .find(|(reg, _)| reg.id() == id)
+            .map(|(_, num)| *num)
+    }
+
+    fn dwarf_stack_reg(&self) -> u16 {
+        2
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{
-    codegen::{AsmPrinter, AssemblyInst},
+    codegen::{Allocation, ArchInfos, AsmPrinter, AssemblyInst, constpool::const_label},
     x86::X86Backend,
 };
 
@@ -18,14 +18,22 @@ impl AsmPrinter for X86Backend {
     fn print_op(&self, op: &crate::codegen::Allocation) -> String {
         match op {
             crate::codegen::Allocation::Register { id, ty } => self.print_reg(id, ty),
-            crate::codegen::Allocation::Stack { slot, ty: _ } => format!("[rsp + {}]", slot * 16),
-            crate::codegen::Allocation::Imm { num, ty: _ } => format!("{num:x}"),
-            crate::codegen::Allocation::ConstUse { id } => format!("[c{id}]"),
+            crate::codegen::Allocation::Stack { slot, ty: _ } => {
+                format!("[rsp + {}]", self.data_layout().slot_offset(*slot))
+            }
+            crate::codegen::Allocation::Imm { num, ty: _ } => format!("{num:#x}"),
+            crate::codegen::Allocation::ConstUse { id } => {
+                format!("qword ptr [rip + {}]", const_label(*id))
+            }
         }
     }
 
     fn print_comment(&self, text: &str) -> String {
-        format!("; {text}\n")
+        format!("# {text}\n")
+    }
+
+    fn print_header(&self) -> &'static str {
+        ".intel_syntax noprefix\n"
     }
 
     #[allow(unreachable_patterns)]
@@ -33,20 +41,19 @@ impl AsmPrinter for X86Backend {
         reg_printer!(num, ty, 0, "rax");
         reg_printer!(num, ty, 1, "rcx");
         reg_printer!(num, ty, 2, "rdx");
-        reg_printer!(num, ty, 3, "rbx");
-        reg_printer!(num, ty, 4, "rsp");
-        reg_printer!(num, ty, 5, "rbp");
-        reg_printer!(num, ty, 6, "rsi");
-        reg_printer!(num, ty, 7, "rdi");
-        reg_printer!(num, ty, 8, "r8");
-        reg_printer!(num, ty, 9, "r9");
-        reg_printer!(num, ty, 10, "r10");
-        reg_printer!(num, ty, 11, "r11");
-        reg_printer!(num, ty, 12, "r12");
-        reg_printer!(num, ty, 13, "r13");
-        reg_printer!(num, ty, 14, "r14");
-        reg_printer!(num, ty, 15, "r15");
-        panic!("Impossible register id: {num}. X86 supports 0-15");
+        reg_printer!(num, ty, 3, "rsi");
+        reg_printer!(num, ty, 4, "rdi");
+        reg_printer!(num, ty, 5, "r8");
+        reg_printer!(num, ty, 6, "r9");
+        reg_printer!(num, ty, 7, "r10");
+        reg_printer!(num, ty, 8, "r11");
+        reg_printer!(num, ty, 9, "rbx");
+        reg_printer!(num, ty, 10, "r12");
+        reg_printer!(num, ty, 11, "r13");
+        reg_printer!(num, ty, 12, "r14");
+        reg_printer!(num, ty, 13, "r15");
+        reg_printer!(num, ty, 14, "rsp");
+        panic!("Impossible register id: {num}. X86 supports 0-14");
     }
 
     fn print_inst(&self, inst: &AssemblyInst) -> String {
@@ -59,6 +66,16 @@ impl AsmPrinter for X86Backend {
             );
         }
 
+        // the size of a stored immediate is taken from the memory operand
+        if let [mem @ Allocation::Stack { .. }, imm @ Allocation::Imm { .. }] = inst.ops[..] {
+            return format!(
+                "\t{} qword ptr {}, {}\n",
+                inst.opcode,
+                self.print_op(&mem),
+                self.print_op(&imm)
+            );
+        }
+
         let mut ops = String::new();
 
         for (index, op) in inst.ops.iter().enumerate() {
@@ -69,10 +86,38 @@ impl AsmPrinter for X86Backend {
             ops += &self.print_op(op);
         }
 
+        if let Some(symbol) = &inst.symbol {
+            if !ops.is_empty() {
+                ops += ", ";
+            }
+
+            ops += symbol;
+        }
+
         format!("\t{} {}\n", inst.opcode, ops)
     }
 
-    fn print_const(&self, c: &crate::codegen::Constant) -> String {
-        format!("c{}: {:?}", c.id, c.bytes)
+    fn print_prologue(&self, func: &crate::codegen::FuncAsm) -> String {
+        if func.frame_size == 0 {
+            return String::new();
+        }
+
+        let mut out = format!("\tsub rsp, {}\n", func.frame_size);
+        for (reg, slot) in &func.saved_regs {
+            out += &format!("\tmov {}, {}\n", self.print_op(slot), self.print_op(reg));
+        }
+        out
+    }
+
+    fn print_epilogue(&self, func: &crate::codegen::FuncAsm) -> String {
+        if func.frame_size == 0 {
+            return String::new();
+        }
+
+        let mut out = String::new();
+        for (reg, slot) in &func.saved_regs {
+            out += &format!("\tmov {}, {}\n", self.print_op(reg), self.print_op(slot));
+        }
+        out + &format!("\tadd rsp, {}\n", func.frame_size)
     }
 }
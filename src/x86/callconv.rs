@@ -0,0 +1,58 @@
+use crate::{
+    codegen::{Allocation, CallConvInfos, CallLowering, Reg},
+    ir::CallingConv,
+    x86::*,
+};
+
+fn regs(regs: &[X86Reg]) -> Vec<Box<dyn Reg>> {
+    regs.iter().map(|x| Box::new(*x) as Box<dyn Reg>).collect()
+}
+
+impl CallConvInfos for X86Backend {
+    fn callconv_arg_regs(&self, conv: CallingConv) -> Option<Vec<Box<dyn Reg>>> {
+        match conv {
+            CallingConv::C | CallingConv::SysV | CallingConv::PreserveMost => {
+                Some(regs(&[RDI, RSI, RDX, RCX, R8, R9]))
+            }
+            CallingConv::Win64 => Some(regs(&[RCX, RDX, R8, R9])),
+            CallingConv::Fast => Some(regs(&[RDI, RSI, RDX, RCX, R8, R9, R10, R11])),
+            CallingConv::Aapcs64 | CallingConv::RiscvLp64 => None,
+        }
+    }
+
+    fn callconv_callee_saved(&self, conv: CallingConv) -> Option<Vec<Box<dyn Reg>>> {
+        match conv {
+            CallingConv::C | CallingConv::SysV | CallingConv::Fast => {
+                Some(regs(&[RBX, R12, R13, R14, R15]))
+            }
+            CallingConv::Win64 => Some(regs(&[RBX, RSI, RDI, R12, R13, R14, R15])),
+            // only rax and r11 are clobbered
+            CallingConv::PreserveMost => Some(regs(&[
+                RCX, RDX, RSI, RDI, R8, R9, R10, RBX, R12, R13, R14, R15,
+            ])),
+            CallingConv::Aapcs64 | CallingConv::RiscvLp64 => None,
+        }
+    }
+
+    fn callconv_shadow_space(&self, conv: CallingConv) -> usize {
+        match conv {
+            CallingConv::Win64 => 32,
+            _ => 0,
+        }
+    }
+}
+
+impl CallLowering for X86Backend {
+    fn call_opcode(&self) -> &'static str {
+        "call"
+    }
+
+    fn call_pushed_bytes(&self) -> usize {
+        8
+    }
+
+    fn move_needs_scratch(&self, from: Allocation, to: Allocation) -> bool {
+        // immediates are stored with `mov`
+        to.is_mem() && from.is_mem()
+    }
+}
@@ -1,7 +1,7 @@
 use procmacro::patterns;
 
 use crate::{
-    codegen::{BackendInst, InstrincLowering, Reg},
+    codegen::{AssemblyInst, BackendInst, InstrincLowering, Reg},
     x86::{RAX, X86Backend},
 };
 
@@ -19,6 +19,14 @@ impl BackendInst for X86Backend {
             condition: in1 != out && in2 != out
             asm: lea (out, in1, in2)
         }
+        Add(Gr, Imm) -> Gr {
+            condition: in1 == out
+            asm: add (in1, in2)
+        }
+        Add(Gr, Imm) -> Gr {
+            condition: in1 != out
+            asm: lea (out, in1, in2)
+        }
         Ret(Gr) {
             condition: in1 == RAX.alloc()
             asm: ret()
@@ -31,6 +39,25 @@ impl BackendInst for X86Backend {
         Copy(Gr) -> Gr {
             asm: mov (out, in1)
         }
+        Copy(Imm) -> Gr {
+            asm: mov (out, in1)
+        }
+        Copy(ConstUse) -> Gr {
+            asm: mov (out, in1)
+        }
+        Copy(Gr) -> Mem {
+            asm: mov (out, in1)
+        }
+        Copy(Imm) -> Mem {
+            asm: mov (out, in1)
+        }
+        Copy(Mem) -> Gr {
+            asm: mov (out, in1)
+        }
+    }
+
+    fn is_noop(&self, inst: &AssemblyInst) -> bool {
+        inst.opcode() == "mov" && inst.ops().len() == 2 && inst.ops()[0] == inst.ops()[1]
     }
 }
 
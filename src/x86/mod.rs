@@ -1,16 +1,41 @@
 //! The x86 backend
+//!
+//! The assembly uses the intel syntax of the GNU assembler (`.intel_syntax noprefix`),
+//! it can't be assembled with NASM
+//!
+//! ```rust
+//! use jacob::codegen::TargetArch;
+//! use jacob::ir::{Function, FunctionAttribute, Module, TypeMetadata};
+//!
+//! let mut module = Module::new();
+//! for name in ["first", "second"] {
+//!     let mut func = Function::new(name);
+//!     let x = func.add_arg(TypeMetadata::Int64);
+//!     func.ret(&x);
+//!     func.add_attr(FunctionAttribute::Section(format!(".text.{name}")));
+//!     module.add_func(func);
+//! }
+//!
+//! let asm = module.compile(TargetArch::X86, false).asm();
+//! assert!(asm.starts_with("# Compilation output\n.intel_syntax noprefix\n.text\n"));
+//! assert_eq!(asm.matches(".intel_syntax").count(), 1);
+//! ```
 
 use procmacro::backend;
 
-use crate::codegen::{Allocation, ArchBackend, ArchInfos, BackendDecompiler, Reg};
+use crate::codegen::{DataLayout, DebugInfos, Reg};
 
 mod asmprinter;
+mod callconv;
 mod lowering;
 
+const LAYOUT: DataLayout = DataLayout::LP64;
+
 backend! {
     name: X86,
     ret_reg: RAX,
     stack_reg: RSP,
+    data_layout: LAYOUT,
 
     caller_saved: [ RAX, RCX, RDX, RSI, RDI, R8, R9, R10, R11 ],
     callee_saved: [
@@ -24,11 +49,41 @@ backend! {
     arg_reg_map: {
         0 -> RDI,
         1 -> RSI,
-        2 -> RCX,
-        3 -> RDX,
+        2 -> RDX,
+        3 -> RCX,
         4 -> R8,
         5 -> R9,
     },
 
     stack_off: -5,
 }
+
+impl DebugInfos for X86Backend {
+    fn dwarf_reg(&self, id: usize) -> Option<u16> {
+        let regs = [
+            (RAX, 0),
+            (RDX, 1),
+            (RCX, 2),
+            (RBX, 3),
+            (RSI, 4),
+            (RDI, 5),
+            (RSP, 7),
+            (R8, 8),
+            (R9, 9),
+            (R10, 10),
+            (R11, 11),
+            (R12, 12),
+            (R13, 13),
+            (R14, 14),
+            (R15, 15),
+        ];
+
+        regs.iter()
+            .find(|(reg, _)| reg.id() == id)
+            .map(|(_, num)| *num)
+    }
+
+    fn dwarf_stack_reg(&self) -> u16 {
+        7
+    }
+}